@@ -0,0 +1,165 @@
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use pyo3::PyErr;
+use tokio::task::JoinHandle;
+
+use crate::row_writer::{ColumnInfo, CompactValue};
+use crate::stream::StreamFrame;
+
+/// A server-side cursor: the pooled client that ran the query stays checked
+/// out on a dedicated blocking-pool thread (the same shape `StreamCursor`
+/// uses for `native_query_stream`), feeding a bounded channel one row at a
+/// time, while `fetchmany` pulls only the rows it was asked for off that
+/// channel instead of a fully materialized result set. Unlike a plain
+/// `native_query_stream` consumer, `fetchmany` batches up to `n` rows into
+/// one `(col_names, values, row_count, col_count)` reply instead of handing
+/// back one frame per call.
+///
+/// Only the first non-empty result set is exposed, matching `do_query`'s
+/// existing single-result-set behavior - `do_query_cursor` skips metadata
+/// frames for empty sets before a `Cursor` is ever constructed, and a second
+/// `Metadata` frame here (the start of the next result set) ends the cursor
+/// the same way `None`/`End` does.
+pub struct Cursor {
+    rx: Arc<Mutex<Receiver<StreamFrame>>>,
+    task: Option<JoinHandle<Result<(), PyErr>>>,
+    cols: Vec<ColumnInfo>,
+    done: bool,
+}
+
+impl Cursor {
+    pub fn new(rx: Receiver<StreamFrame>, task: JoinHandle<Result<(), PyErr>>, cols: Vec<ColumnInfo>) -> Self {
+        Self { rx: Arc::new(Mutex::new(rx)), task: Some(task), cols, done: false }
+    }
+
+    /// Block (off the async runtime's worker thread) until the background
+    /// task's next frame arrives, or `None` once the channel has nothing
+    /// left to give.
+    async fn recv(&self) -> Option<StreamFrame> {
+        let rx = self.rx.clone();
+        tokio::task::spawn_blocking(move || rx.lock().recv().ok())
+            .await
+            .expect("cursor receiver thread panicked")
+    }
+
+    /// Join the background batch task, surfacing its error (if any) here
+    /// instead of letting it vanish with a dropped `JoinHandle`.
+    async fn finish(&mut self) -> Result<(), PyErr> {
+        self.done = true;
+        if let Some(task) = self.task.take() {
+            match task.await {
+                Ok(result) => return result,
+                Err(e) => panic!("cursor query task panicked: {e}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Return up to `n` more rows as `(col_names, values, row_count, col_count)`,
+    /// or `None` once the result set is exhausted.
+    pub async fn fetchmany(&mut self, n: usize) -> Result<Option<(Vec<String>, Vec<CompactValue>, usize, usize)>, PyErr> {
+        if self.done {
+            return Ok(None);
+        }
+        let col_count = self.cols.len();
+        if n == 0 {
+            let col_names: Vec<String> = self.cols.iter().map(|c| c.name.clone()).collect();
+            return Ok(Some((col_names, Vec::new(), 0, col_count)));
+        }
+        let mut values = Vec::new();
+        let mut row_count = 0usize;
+        while row_count < n {
+            match self.recv().await {
+                Some(StreamFrame::Row(row)) => {
+                    values.extend(row);
+                    row_count += 1;
+                }
+                Some(StreamFrame::Info { .. }) => continue,
+                Some(StreamFrame::Metadata(_)) | None | Some(StreamFrame::End) => {
+                    self.finish().await?;
+                    break;
+                }
+            }
+        }
+        if row_count == 0 {
+            return Ok(None);
+        }
+        let col_names: Vec<String> = self.cols.iter().map(|c| c.name.clone()).collect();
+        Ok(Some((col_names, values, row_count, col_count)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cols(names: &[&str]) -> Vec<ColumnInfo> {
+        names.iter().map(|n| ColumnInfo { name: n.to_string() }).collect()
+    }
+
+    fn make_cursor(cols: Vec<ColumnInfo>) -> (std::sync::mpsc::SyncSender<StreamFrame>, Cursor) {
+        let (tx, rx) = std::sync::mpsc::sync_channel(16);
+        let task = tokio::spawn(async { Ok::<(), PyErr>(()) });
+        (tx, Cursor::new(rx, task, cols))
+    }
+
+    #[tokio::test]
+    async fn fetchmany_zero_returns_an_empty_batch_without_touching_the_channel() {
+        let (_tx, mut cursor) = make_cursor(cols(&["a"]));
+        let (col_names, values, row_count, col_count) = cursor.fetchmany(0).await.unwrap().expect("not exhausted");
+        assert_eq!(col_names, vec!["a"]);
+        assert!(values.is_empty());
+        assert_eq!(row_count, 0);
+        assert_eq!(col_count, 1);
+    }
+
+    #[tokio::test]
+    async fn fetchmany_collects_up_to_n_rows_across_multiple_calls() {
+        let (tx, mut cursor) = make_cursor(cols(&["a"]));
+        tx.send(StreamFrame::Row(vec![CompactValue::I64(1)])).unwrap();
+        tx.send(StreamFrame::Row(vec![CompactValue::I64(2)])).unwrap();
+        tx.send(StreamFrame::Row(vec![CompactValue::I64(3)])).unwrap();
+        tx.send(StreamFrame::End).unwrap();
+
+        let (_, values, row_count, _) = cursor.fetchmany(2).await.unwrap().expect("not exhausted");
+        assert_eq!(row_count, 2);
+        assert!(matches!(values[0], CompactValue::I64(1)));
+        assert!(matches!(values[1], CompactValue::I64(2)));
+
+        let (_, values, row_count, _) = cursor.fetchmany(2).await.unwrap().expect("not exhausted");
+        assert_eq!(row_count, 1);
+        assert!(matches!(values[0], CompactValue::I64(3)));
+    }
+
+    #[tokio::test]
+    async fn fetchmany_skips_info_frames_without_counting_them_as_rows() {
+        let (tx, mut cursor) = make_cursor(cols(&["a"]));
+        tx.send(StreamFrame::Info { number: 0, message: "notice".to_string() }).unwrap();
+        tx.send(StreamFrame::Row(vec![CompactValue::I64(7)])).unwrap();
+
+        let (_, values, row_count, _) = cursor.fetchmany(1).await.unwrap().expect("not exhausted");
+        assert_eq!(row_count, 1);
+        assert!(matches!(values[0], CompactValue::I64(7)));
+    }
+
+    #[tokio::test]
+    async fn fetchmany_returns_a_short_batch_then_none_once_the_stream_ends() {
+        let (tx, mut cursor) = make_cursor(cols(&["a"]));
+        tx.send(StreamFrame::Row(vec![CompactValue::I64(1)])).unwrap();
+        tx.send(StreamFrame::End).unwrap();
+
+        let (_, _, row_count, _) = cursor.fetchmany(5).await.unwrap().expect("one row before end");
+        assert_eq!(row_count, 1);
+
+        assert!(cursor.fetchmany(5).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn fetchmany_on_an_empty_closed_channel_returns_none() {
+        let (tx, mut cursor) = make_cursor(cols(&["a"]));
+        drop(tx);
+        assert!(cursor.fetchmany(5).await.unwrap().is_none());
+    }
+}
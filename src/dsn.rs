@@ -0,0 +1,214 @@
+use std::time::Duration;
+
+/// Parsed ADO.NET-style connection string.
+///
+/// Keeping every recognized knob on one struct (rather than returning a
+/// growing tuple from the parser) is what lets `do_connect` and `Pool` share
+/// a single source of truth as we pick up more DSN keys over time.
+#[derive(Clone, Debug)]
+pub struct ConnectionOptions {
+    pub host: String,
+    pub port: u16,
+    pub database: String,
+    pub uid: String,
+    pub pwd: String,
+    pub trust_cert: bool,
+    pub max_pool_size: u32,
+    pub min_pool_size: u32,
+    pub connection_lifetime: Option<Duration>,
+    pub connect_retry_count: u32,
+    pub connect_retry_interval: Duration,
+    /// Escape hatch back to the old literal-substitution parameter path;
+    /// the default is the `sp_executesql` RPC path.
+    pub legacy_literal_params: bool,
+    /// Max number of `sp_prepare` handles to keep per session. `0` disables
+    /// the prepared-statement cache entirely.
+    pub statement_cache_size: u32,
+    /// Once a result set buffered in memory crosses this many bytes, spill it
+    /// (and the rest of that result set) to a temp file instead of growing
+    /// the in-process buffer further. `0` disables spilling, buffering every
+    /// result set in memory regardless of size.
+    pub spill_threshold_bytes: u32,
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1433,
+            database: "master".to_string(),
+            uid: String::new(),
+            pwd: String::new(),
+            trust_cert: false,
+            max_pool_size: 100,
+            min_pool_size: 0,
+            connection_lifetime: None,
+            connect_retry_count: 1,
+            connect_retry_interval: Duration::from_secs(10),
+            legacy_literal_params: false,
+            statement_cache_size: 128,
+            spill_threshold_bytes: 0,
+        }
+    }
+}
+
+pub fn parse_connection_string(conn_str: &str) -> ConnectionOptions {
+    let mut opts = ConnectionOptions::default();
+
+    for part in conn_str.split(';') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        if let Some(idx) = part.find('=') {
+            let key = part[..idx].trim().to_lowercase();
+            let val = part[idx + 1..].trim().to_string();
+            match key.as_str() {
+                "server" => {
+                    if let Some(comma) = val.find(',') {
+                        opts.host = val[..comma].to_string();
+                        if let Ok(p) = val[comma + 1..].trim().parse() {
+                            opts.port = p;
+                        }
+                    } else {
+                        opts.host = val;
+                    }
+                }
+                "database" | "initial catalog" => opts.database = val,
+                "uid" | "user id" => opts.uid = val,
+                "pwd" | "password" => opts.pwd = val,
+                "trustservercertificate" => {
+                    opts.trust_cert = val.eq_ignore_ascii_case("yes")
+                        || val == "1"
+                        || val.eq_ignore_ascii_case("true")
+                }
+                "max pool size" => {
+                    if let Ok(v) = val.parse() {
+                        opts.max_pool_size = v;
+                    }
+                }
+                "min pool size" => {
+                    if let Ok(v) = val.parse() {
+                        opts.min_pool_size = v;
+                    }
+                }
+                "connection lifetime" => {
+                    if let Ok(secs) = val.parse::<u64>() {
+                        opts.connection_lifetime =
+                            if secs == 0 { None } else { Some(Duration::from_secs(secs)) };
+                    }
+                }
+                "connectretrycount" => {
+                    if let Ok(v) = val.parse() {
+                        opts.connect_retry_count = v;
+                    }
+                }
+                "connectretryinterval" => {
+                    if let Ok(secs) = val.parse::<u64>() {
+                        opts.connect_retry_interval = Duration::from_secs(secs);
+                    }
+                }
+                "legacy literal params" => {
+                    opts.legacy_literal_params = val.eq_ignore_ascii_case("yes")
+                        || val == "1"
+                        || val.eq_ignore_ascii_case("true")
+                }
+                "statement cache size" => {
+                    if let Ok(v) = val.parse() {
+                        opts.statement_cache_size = v;
+                    }
+                }
+                "result set spill threshold" => {
+                    if let Ok(v) = val.parse() {
+                        opts.spill_threshold_bytes = v;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    opts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unrecognized_dsn_leaves_every_option_at_its_default() {
+        let opts = parse_connection_string("");
+        assert_eq!(opts.host, ConnectionOptions::default().host);
+        assert_eq!(opts.max_pool_size, ConnectionOptions::default().max_pool_size);
+    }
+
+    #[test]
+    fn server_key_splits_host_and_port_on_comma() {
+        let opts = parse_connection_string("Server=db.example.com,1434");
+        assert_eq!(opts.host, "db.example.com");
+        assert_eq!(opts.port, 1434);
+    }
+
+    #[test]
+    fn server_key_without_a_port_leaves_the_default_port() {
+        let opts = parse_connection_string("Server=db.example.com");
+        assert_eq!(opts.host, "db.example.com");
+        assert_eq!(opts.port, ConnectionOptions::default().port);
+    }
+
+    #[test]
+    fn pool_size_keys_parse_independently() {
+        let opts = parse_connection_string("Max Pool Size=5;Min Pool Size=2");
+        assert_eq!(opts.max_pool_size, 5);
+        assert_eq!(opts.min_pool_size, 2);
+    }
+
+    #[test]
+    fn connection_lifetime_zero_means_no_expiry() {
+        let opts = parse_connection_string("Connection Lifetime=0");
+        assert!(opts.connection_lifetime.is_none());
+    }
+
+    #[test]
+    fn connection_lifetime_nonzero_sets_a_duration() {
+        let opts = parse_connection_string("Connection Lifetime=120");
+        assert_eq!(opts.connection_lifetime, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn connect_retry_keys_parse_count_and_interval() {
+        let opts = parse_connection_string("ConnectRetryCount=3;ConnectRetryInterval=5");
+        assert_eq!(opts.connect_retry_count, 3);
+        assert_eq!(opts.connect_retry_interval, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn legacy_literal_params_accepts_yes_1_and_true() {
+        for val in ["yes", "1", "true", "TRUE"] {
+            let opts = parse_connection_string(&format!("Legacy Literal Params={val}"));
+            assert!(opts.legacy_literal_params, "expected {val} to enable legacy literal params");
+        }
+        let opts = parse_connection_string("Legacy Literal Params=no");
+        assert!(!opts.legacy_literal_params);
+    }
+
+    #[test]
+    fn statement_cache_size_and_spill_threshold_parse_as_integers() {
+        let opts = parse_connection_string("Statement Cache Size=256;Result Set Spill Threshold=1048576");
+        assert_eq!(opts.statement_cache_size, 256);
+        assert_eq!(opts.spill_threshold_bytes, 1_048_576);
+    }
+
+    #[test]
+    fn keys_are_case_insensitive_and_whitespace_tolerant() {
+        let opts = parse_connection_string(" DATABASE = mydb ; UID=sa ; PWD = secret ");
+        assert_eq!(opts.database, "mydb");
+        assert_eq!(opts.uid, "sa");
+        assert_eq!(opts.pwd, "secret");
+    }
+
+    #[test]
+    fn unparseable_numeric_value_falls_back_to_the_default() {
+        let opts = parse_connection_string("Max Pool Size=not-a-number");
+        assert_eq!(opts.max_pool_size, ConnectionOptions::default().max_pool_size);
+    }
+}
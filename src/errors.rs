@@ -1,11 +1,166 @@
 use pyo3::prelude::*;
 use tabby::error::Error as TabbyError;
 
+/// PEP-249 exception family, registered as real Python classes on `hiss_native`.
+///
+/// `Error` is the root of everything a DB-API consumer is expected to catch;
+/// `Warning` sits outside that hierarchy per the spec. The `Database*` leaves
+/// are what `to_pyerr` actually raises - `InterfaceError` is reserved for
+/// failures in this binding itself rather than the server.
+pyo3::create_exception!(hiss_native, Warning, pyo3::exceptions::PyException);
+pyo3::create_exception!(hiss_native, Error, pyo3::exceptions::PyException);
+pyo3::create_exception!(hiss_native, InterfaceError, Error);
+pyo3::create_exception!(hiss_native, DatabaseError, Error);
+pyo3::create_exception!(hiss_native, DataError, DatabaseError);
+pyo3::create_exception!(hiss_native, OperationalError, DatabaseError);
+pyo3::create_exception!(hiss_native, IntegrityError, DatabaseError);
+pyo3::create_exception!(hiss_native, InternalError, DatabaseError);
+pyo3::create_exception!(hiss_native, ProgrammingError, DatabaseError);
+pyo3::create_exception!(hiss_native, NotSupportedError, DatabaseError);
+
+pub fn register_exceptions(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("Warning", py.get_type::<Warning>())?;
+    m.add("Error", py.get_type::<Error>())?;
+    m.add("InterfaceError", py.get_type::<InterfaceError>())?;
+    m.add("DatabaseError", py.get_type::<DatabaseError>())?;
+    m.add("DataError", py.get_type::<DataError>())?;
+    m.add("OperationalError", py.get_type::<OperationalError>())?;
+    m.add("IntegrityError", py.get_type::<IntegrityError>())?;
+    m.add("InternalError", py.get_type::<InternalError>())?;
+    m.add("ProgrammingError", py.get_type::<ProgrammingError>())?;
+    m.add("NotSupportedError", py.get_type::<NotSupportedError>())?;
+    Ok(())
+}
+
+/// Which DB-API leaf a given SQL Server engine error number maps to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorCategory {
+    Data,
+    Operational,
+    Integrity,
+    Internal,
+    Programming,
+    NotSupported,
+}
+
+/// SQL Server engine error numbers we know how to classify precisely.
+///
+/// Numbers not present here fall back to a severity-class based guess in
+/// `category_for`. Sourced from sys.messages for the error numbers most
+/// commonly hit in application code (constraint violations, invalid object
+/// references, deadlocks, and arithmetic errors).
+static ERROR_NUMBER_MAP: phf::Map<i32, ErrorCategory> = phf::phf_map! {
+    2601i32 => ErrorCategory::Integrity, // duplicate key on unique index
+    2627i32 => ErrorCategory::Integrity, // primary/unique constraint violation
+    547i32 => ErrorCategory::Integrity,  // foreign key / check constraint violation
+    515i32 => ErrorCategory::Integrity,  // cannot insert NULL into non-nullable column
+    208i32 => ErrorCategory::Programming, // invalid object name
+    207i32 => ErrorCategory::Programming, // invalid column name
+    102i32 => ErrorCategory::Programming, // syntax error near
+    156i32 => ErrorCategory::Programming, // incorrect syntax near keyword
+    201i32 => ErrorCategory::Programming, // procedure expects parameter which was not supplied
+    2812i32 => ErrorCategory::Programming, // could not find stored procedure
+    1205i32 => ErrorCategory::Operational, // transaction deadlock victim
+    1222i32 => ErrorCategory::Operational, // lock request timeout
+    233i32 => ErrorCategory::Operational,  // no process on other end of pipe / connection reset
+    -2i32 => ErrorCategory::Operational,   // client-side command timeout
+    40508i32 => ErrorCategory::NotSupported, // USE statement not supported (Azure SQL Database)
+    40515i32 => ErrorCategory::NotSupported, // cross-database reference not supported (Azure SQL Database)
+    8134i32 => ErrorCategory::Data, // divide by zero
+    8115i32 => ErrorCategory::Data, // arithmetic overflow
+    245i32 => ErrorCategory::Data,  // conversion failed
+    8152i32 => ErrorCategory::Data, // string or binary data would be truncated
+};
+
+/// Fallback when the engine error number isn't in `ERROR_NUMBER_MAP`, based on
+/// SQL Server's severity classes (sys.messages.severity).
+fn category_for(number: i32, severity: u8) -> ErrorCategory {
+    if let Some(cat) = ERROR_NUMBER_MAP.get(&number) {
+        return *cat;
+    }
+    match severity {
+        0..=10 => ErrorCategory::Programming, // informational / user-correctable
+        11..=16 => ErrorCategory::Programming, // user/statement errors
+        17..=19 => ErrorCategory::Operational, // resource errors (out of space, memory)
+        _ => ErrorCategory::Internal,          // 20+: fatal, connection-ending
+    }
+}
+
+fn raise_database_error(py: Python<'_>, cat: ErrorCategory, msg: String, fields: &[(&str, PyObject)]) -> PyErr {
+    let err: PyErr = match cat {
+        ErrorCategory::Data => DataError::new_err(msg),
+        ErrorCategory::Operational => OperationalError::new_err(msg),
+        ErrorCategory::Integrity => IntegrityError::new_err(msg),
+        ErrorCategory::Internal => InternalError::new_err(msg),
+        ErrorCategory::Programming => ProgrammingError::new_err(msg),
+        ErrorCategory::NotSupported => NotSupportedError::new_err(msg),
+    };
+    let value = err.value(py);
+    for (name, val) in fields {
+        let _ = value.setattr(*name, val);
+    }
+    err
+}
+
+/// True for failures that a fresh connection is likely to recover from: a
+/// dropped socket or handshake timeout rather than a server-rejected batch.
+/// Used to decide whether `do_query`/`do_execute` may transparently
+/// reconnect and retry once instead of surfacing the error immediately.
+pub fn is_transient(e: &TabbyError) -> bool {
+    matches!(e, TabbyError::Io { .. })
+}
+
 pub fn to_pyerr(e: TabbyError) -> PyErr {
     let msg = format!("{}", e);
     match &e {
-        TabbyError::Server(_) => pyo3::exceptions::PyRuntimeError::new_err(msg),
-        TabbyError::Io { .. } => pyo3::exceptions::PyConnectionError::new_err(msg),
-        _ => pyo3::exceptions::PyRuntimeError::new_err(msg),
+        TabbyError::Server(token) => Python::with_gil(|py| {
+            let number = token.code() as i32;
+            let severity = token.class();
+            let cat = category_for(number, severity);
+            let fields: Vec<(&str, PyObject)> = vec![
+                ("number", number.into_pyobject(py).unwrap().into_any().unbind()),
+                ("severity", severity.into_pyobject(py).unwrap().into_any().unbind()),
+                ("state", token.state().into_pyobject(py).unwrap().into_any().unbind()),
+                ("line", token.line_number().into_pyobject(py).unwrap().into_any().unbind()),
+                ("procedure", token.procedure().into_pyobject(py).unwrap().into_any().unbind()),
+                ("server", token.server().into_pyobject(py).unwrap().into_any().unbind()),
+            ];
+            raise_database_error(py, cat, msg, &fields)
+        }),
+        // `Io` (a dropped socket or handshake timeout) falls through here
+        // too: it's a transport failure, not a driver-usage mistake, so
+        // `OperationalError` per PEP-249 - the same class `is_transient`
+        // already treats as worth a transparent retry.
+        _ => OperationalError::new_err(msg),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_error_numbers_take_precedence_over_severity() {
+        // Severity 16 alone would guess Programming; the map entry for a
+        // duplicate key should win instead.
+        assert_eq!(category_for(2627, 16), ErrorCategory::Integrity);
+        assert_eq!(category_for(40508, 20), ErrorCategory::NotSupported);
+    }
+
+    #[test]
+    fn unknown_error_numbers_fall_back_to_severity_class() {
+        assert_eq!(category_for(999999, 5), ErrorCategory::Programming);
+        assert_eq!(category_for(999999, 16), ErrorCategory::Programming);
+        assert_eq!(category_for(999999, 18), ErrorCategory::Operational);
+        assert_eq!(category_for(999999, 20), ErrorCategory::Internal);
+    }
+
+    #[test]
+    fn severity_class_boundaries_land_on_the_right_side() {
+        assert_eq!(category_for(999999, 10), ErrorCategory::Programming);
+        assert_eq!(category_for(999999, 11), ErrorCategory::Programming);
+        assert_eq!(category_for(999999, 17), ErrorCategory::Operational);
+        assert_eq!(category_for(999999, 19), ErrorCategory::Operational);
     }
 }
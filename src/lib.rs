@@ -3,25 +3,91 @@ use pyo3::prelude::*;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tabby::{AuthMethod, Client, Config, EncryptionLevel};
-use tokio::net::TcpStream;
-use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
 
+mod cursor;
+mod dsn;
 mod errors;
+mod msgpack_writer;
+mod pool;
 mod row_writer;
+mod spill;
+mod stmt_cache;
+mod stream;
 mod types;
 
-use errors::to_pyerr;
+use cursor::Cursor;
+use dsn::parse_connection_string;
+use errors::{is_transient, register_exceptions, to_pyerr};
+use msgpack_writer::MsgPackRowWriter;
+use pool::{Pool, PoolConfig, PooledClient};
 use row_writer::{CompactValue, MultiSetWriter};
-use types::{compact_value_to_py, py_to_sql_literal};
+use stream::{StreamCursor, StreamFrame, StreamingRowWriter};
+use tabby::ColumnData;
+use types::{compact_value_to_py, py_to_sql_literal, py_to_tds_param};
+
+/// Either a literal-substituted batch (legacy path) or a `sp_executesql` RPC
+/// call with real typed parameters bound positionally to `@p1`, `@p2`, ...
+/// placeholders already in the SQL text.
+enum Statement {
+    Literal(String),
+    Rpc(String, Vec<ColumnData<'static>>),
+}
 
-type TdsClient = Client<Compat<TcpStream>>;
+impl Statement {
+    fn sql(&self) -> &str {
+        match self {
+            Statement::Literal(sql) | Statement::Rpc(sql, _) => sql,
+        }
+    }
+
+    fn append(self, suffix: &str) -> Statement {
+        match self {
+            Statement::Literal(sql) => Statement::Literal(format!("{}\n{}", sql, suffix)),
+            Statement::Rpc(sql, params) => Statement::Rpc(format!("{}\n{}", sql, suffix), params),
+        }
+    }
+}
+
+/// Build a `Statement` for `sql`/`params` according to the pool's parameter
+/// mode: the `sp_executesql` RPC path by default, or literal substitution
+/// for `Legacy Literal Params=true` connections.
+fn build_statement(py: Python<'_>, pool: &Pool, sql: String, params: &[PyObject]) -> PyResult<Statement> {
+    if params.is_empty() {
+        return Ok(Statement::Literal(sql));
+    }
+    if pool.uses_legacy_literal_params() {
+        return Ok(Statement::Literal(substitute_params(py, &sql, params)?));
+    }
+    let tds_params: Vec<ColumnData<'static>> = params
+        .iter()
+        .map(|p| py_to_tds_param(py, p.bind(py)))
+        .collect::<PyResult<_>>()?;
+    Ok(Statement::Rpc(sql, tds_params))
+}
 
 static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_CURSOR_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A client pinned to one `conn_id` by `native_pin_session`, parked here
+/// until `native_unpin_session` (or a failed batch) lets it go.
+///
+/// `Pinning` is a placeholder `do_pin_session` inserts *before* awaiting
+/// `pool.acquire()`, so the whole check-then-acquire is atomic across that
+/// await: a second concurrent pin attempt sees the placeholder and bails out
+/// instead of also passing a `contains_key` check and acquiring a second
+/// client/permit for the same `conn_id`.
+enum PinSlot {
+    Pinning,
+    Ready(PooledClient),
+}
 
 struct Bridge {
     rt: tokio::runtime::Runtime,
-    connections: Mutex<HashMap<u64, Arc<tokio::sync::Mutex<TdsClient>>>>,
+    connections: Mutex<HashMap<u64, Arc<Pool>>>,
+    cursors: Mutex<HashMap<u64, Cursor>>,
+    streams: Mutex<HashMap<u64, StreamCursor>>,
+    pinned: Mutex<HashMap<u64, PinSlot>>,
 }
 
 static BRIDGE: std::sync::LazyLock<Bridge> = std::sync::LazyLock::new(|| Bridge {
@@ -31,85 +97,93 @@ static BRIDGE: std::sync::LazyLock<Bridge> = std::sync::LazyLock::new(|| Bridge
         .build()
         .expect("Failed to create tokio runtime"),
     connections: Mutex::new(HashMap::new()),
+    cursors: Mutex::new(HashMap::new()),
+    streams: Mutex::new(HashMap::new()),
+    pinned: Mutex::new(HashMap::new()),
 });
 
-fn parse_connection_string(conn_str: &str) -> (String, u16, String, String, String, bool) {
-    let mut host = "localhost".to_string();
-    let mut port: u16 = 1433;
-    let mut database = "master".to_string();
-    let mut uid = String::new();
-    let mut pwd = String::new();
-    let mut trust_cert = false;
-
-    for part in conn_str.split(';') {
-        let part = part.trim();
-        if part.is_empty() {
-            continue;
+/// A client checked out for one call: either the session pinned to this
+/// `conn_id` by `native_pin_session`, or an ordinary fresh checkout when
+/// nothing is pinned. Every `run_batch`-style call site goes through
+/// `acquire_for`/`release_lease`/`discard_lease` instead of `pool.acquire()`
+/// directly so a pinned session is reused instead of raced for a second one.
+enum ClientLease {
+    Pinned(PooledClient),
+    Owned(PooledClient),
+}
+
+impl ClientLease {
+    fn client(&mut self) -> &mut PooledClient {
+        match self {
+            ClientLease::Pinned(c) | ClientLease::Owned(c) => c,
         }
-        if let Some(idx) = part.find('=') {
-            let key = part[..idx].trim().to_lowercase();
-            let val = part[idx + 1..].trim().to_string();
-            match key.as_str() {
-                "server" => {
-                    if let Some(comma) = val.find(',') {
-                        host = val[..comma].to_string();
-                        if let Ok(p) = val[comma + 1..].trim().parse() {
-                            port = p;
-                        }
-                    } else {
-                        host = val;
-                    }
-                }
-                "database" | "initial catalog" => database = val,
-                "uid" | "user id" => uid = val,
-                "pwd" | "password" => pwd = val,
-                "trustservercertificate" => {
-                    trust_cert = val.eq_ignore_ascii_case("yes")
-                        || val == "1"
-                        || val.eq_ignore_ascii_case("true")
-                }
-                _ => {}
-            }
+    }
+
+    fn is_pinned(&self) -> bool {
+        matches!(self, ClientLease::Pinned(_))
+    }
+}
+
+/// Check out a client for `conn_id`: the one `native_pin_session` parked
+/// here if there is one, otherwise a fresh checkout from `pool` - exactly
+/// what every call did before pinning existed.
+async fn acquire_for(conn_id: u64, pool: &Arc<Pool>) -> Result<ClientLease, PyErr> {
+    match BRIDGE.pinned.lock().remove(&conn_id) {
+        Some(PinSlot::Ready(client)) => Ok(ClientLease::Pinned(client)),
+        Some(PinSlot::Pinning) => {
+            // A concurrent `native_pin_session` call hasn't finished
+            // acquiring its client yet - put the placeholder back and fall
+            // back to an ordinary checkout rather than racing it.
+            BRIDGE.pinned.lock().insert(conn_id, PinSlot::Pinning);
+            Ok(ClientLease::Owned(pool.acquire().await?))
         }
+        None => Ok(ClientLease::Owned(pool.acquire().await?)),
     }
-    (host, port, database, uid, pwd, trust_cert)
+}
+
+/// Return a lease after a successful batch: a pinned client goes back into
+/// the `Bridge` for the next call on this `conn_id`; an ordinary one just
+/// drops here, returning itself to `pool` the way it always has.
+fn release_lease(conn_id: u64, lease: ClientLease) {
+    if let ClientLease::Pinned(client) = lease {
+        BRIDGE.pinned.lock().insert(conn_id, PinSlot::Ready(client));
+    }
+}
+
+/// Drop a lease after a failed batch, calling `discard()` on its client so it
+/// never goes back to the pool. A pinned lease is deliberately *not* reinserted here -
+/// a session that failed mid-batch can't be trusted for whatever this
+/// `conn_id` was about to run next, so the pin breaks and a later call needs
+/// `native_pin_session` again.
+fn discard_lease(mut lease: ClientLease) {
+    lease.client().discard();
 }
 
 /// Result from a query: column names + flat values + row_count + col_count
 type QueryResult = Option<(Vec<String>, Vec<CompactValue>, usize, usize)>;
 
 async fn do_connect(dsn: String) -> Result<u64, PyErr> {
-    let (host, port, database, uid, pwd, trust_cert) = parse_connection_string(&dsn);
-    let mut config = Config::new();
-    config.host(&host);
-    config.port(port);
-    config.database(&database);
-    config.authentication(AuthMethod::sql_server(&uid, &pwd));
-    if trust_cert {
-        config.trust_cert();
-    }
-    config.encryption(EncryptionLevel::Required);
-
-    let tcp = TcpStream::connect(config.get_addr()).await.map_err(|e| {
-        pyo3::exceptions::PyConnectionError::new_err(format!("TCP connect failed: {}", e))
-    })?;
-    tcp.set_nodelay(true)
-        .map_err(|e| pyo3::exceptions::PyConnectionError::new_err(format!("{}", e)))?;
-    let client = Client::connect(config, tcp.compat_write())
-        .await
-        .map_err(|e| {
-            pyo3::exceptions::PyConnectionError::new_err(format!("TDS connect failed: {}", e))
-        })?;
+    let opts = parse_connection_string(&dsn);
+    let max_size = opts.max_pool_size.max(1);
+    let pool_config = PoolConfig {
+        // A DSN with `Min Pool Size` > `Max Pool Size` would otherwise warm
+        // up (and keep health-checking back up to) more idle connections
+        // than `Max Pool Size` is supposed to cap - only concurrent
+        // checkouts are bounded by the semaphore, not `Pool::new`'s and
+        // `run_health_check`'s top-up loops.
+        min_size: opts.min_pool_size.min(max_size),
+        max_size,
+        connection_lifetime: opts.connection_lifetime,
+    };
+    let pool = Pool::new(opts, pool_config).await?;
+    pool.spawn_health_check(&BRIDGE.rt);
 
     let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
-    BRIDGE
-        .connections
-        .lock()
-        .insert(id, Arc::new(tokio::sync::Mutex::new(client)));
+    BRIDGE.connections.lock().insert(id, pool);
     Ok(id)
 }
 
-fn get_conn(id: u64) -> PyResult<Arc<tokio::sync::Mutex<TdsClient>>> {
+fn get_conn(id: u64) -> PyResult<Arc<Pool>> {
     BRIDGE
         .connections
         .lock()
@@ -118,24 +192,60 @@ fn get_conn(id: u64) -> PyResult<Arc<tokio::sync::Mutex<TdsClient>>> {
         .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Connection is closed"))
 }
 
-async fn do_query(id: u64, sql: String) -> Result<QueryResult, PyErr> {
-    let conn = get_conn(id)?;
-    let mut c = conn.lock().await;
-    let mut msw = MultiSetWriter::new();
-    c.batch_into(&sql, &mut msw).await.map_err(to_pyerr)?;
-    drop(c);
-    let sets = msw.finalize();
-    for (cols, writer) in &sets {
+/// Run one batch against a pooled client, transparently reconnecting and
+/// retrying once if the failure looks like a dropped socket rather than a
+/// server-rejected batch. Shared by every entry point that used to do its
+/// own `acquire` + `batch_into` + discard-on-error dance.
+///
+/// Honors a client `native_pin_session` has pinned to `conn_id`, if any, via
+/// `acquire_for` - and never retries a pinned session's failure, since a
+/// transparent reconnect would land the rest of the pinned run on a
+/// different physical session than whatever the caller already did on it,
+/// exactly the bug pinning exists to prevent.
+async fn run_batch(conn_id: u64, pool: &Arc<Pool>, stmt: &Statement) -> Result<MultiSetWriter, PyErr> {
+    let mut retried = false;
+    loop {
+        let mut lease = acquire_for(conn_id, pool).await?;
+        let mut msw = match pool.spill_threshold_bytes() {
+            0 => MultiSetWriter::new(),
+            n => MultiSetWriter::with_spill_threshold(n as usize),
+        };
+        let outcome = match stmt {
+            Statement::Literal(sql) => lease.client().batch_into(sql, &mut msw).await,
+            Statement::Rpc(sql, params) => lease.client().rpc_query_cached_into(sql, params, &mut msw).await,
+        };
+        match outcome {
+            Ok(()) => {
+                release_lease(conn_id, lease);
+                return Ok(msw);
+            }
+            Err(e) => {
+                let pinned = lease.is_pinned();
+                discard_lease(lease);
+                if !retried && !pinned && is_transient(&e) {
+                    retried = true;
+                    continue;
+                }
+                return Err(to_pyerr(e));
+            }
+        }
+    }
+}
+
+async fn do_query(conn_id: u64, pool: Arc<Pool>, stmt: Statement) -> Result<QueryResult, PyErr> {
+    let msw = run_batch(conn_id, &pool, &stmt).await?;
+    let mut sets = msw.finalize();
+    for (cols, writer) in &mut sets {
         if cols.is_empty() {
             continue;
         }
         let col_names: Vec<String> = cols.iter().map(|c| c.name.clone()).collect();
         let row_count = writer.row_count();
-        let col_count = writer.col_count;
+        let col_count = writer.col_count();
         let mut values: Vec<CompactValue> = Vec::with_capacity(row_count * col_count);
         for r in 0..row_count {
             for c in 0..col_count {
-                values.push(writer.get(r, c).clone());
+                values.push(writer.get(r, c));
             }
         }
         return Ok(Some((col_names, values, row_count, col_count)));
@@ -143,31 +253,201 @@ async fn do_query(id: u64, sql: String) -> Result<QueryResult, PyErr> {
     Ok(None)
 }
 
-async fn do_execute(id: u64, sql: String) -> Result<String, PyErr> {
-    let conn = get_conn(id)?;
-    let trimmed = sql.trim().to_uppercase();
+/// Like `do_query`, but the result set never touches `CompactValue` at all -
+/// `MsgPackRowWriter` serializes each cell straight to MessagePack bytes, for
+/// callers that want to cache query output, ship it over IPC, or hand it to
+/// a non-Python consumer instead of paying the pyo3 conversion cost.
+async fn do_query_msgpack(conn_id: u64, pool: Arc<Pool>, stmt: Statement) -> Result<Vec<u8>, PyErr> {
+    let mut retried = false;
+    loop {
+        let mut lease = acquire_for(conn_id, &pool).await?;
+        let mut writer = MsgPackRowWriter::new();
+        let outcome = match &stmt {
+            Statement::Literal(sql) => lease.client().batch_into(sql, &mut writer).await,
+            Statement::Rpc(sql, params) => lease.client().rpc_query_cached_into(sql, params, &mut writer).await,
+        };
+        match outcome {
+            Ok(()) => {
+                release_lease(conn_id, lease);
+                return Ok(writer.finalize());
+            }
+            Err(e) => {
+                let pinned = lease.is_pinned();
+                discard_lease(lease);
+                if !retried && !pinned && is_transient(&e) {
+                    retried = true;
+                    continue;
+                }
+                return Err(to_pyerr(e));
+            }
+        }
+    }
+}
+
+/// Start a server-side cursor: like `do_query_stream`, the batch runs to
+/// completion on a dedicated blocking-pool thread feeding a bounded channel,
+/// so `fetchmany` can pull only the rows it was asked for off the wire
+/// instead of `do_query`'s old shape of draining the entire result set into
+/// a `RowSet` before the cursor even existed. This call itself only blocks
+/// long enough to see the first non-empty result set's metadata (skipping
+/// past any empty ones ahead of it, same as the old `RowSet`-based cursor
+/// did) - the rows behind it stream in as `fetchmany` asks for them.
+async fn do_query_cursor(conn_id: u64, pool: Arc<Pool>, stmt: Statement) -> Result<u64, PyErr> {
+    let mut lease = acquire_for(conn_id, &pool).await?;
+    let (mut writer, rx) = StreamingRowWriter::new();
+
+    let task = tokio::task::spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(async move {
+            let outcome = match &stmt {
+                Statement::Literal(sql) => lease.client().batch_into(sql, &mut writer).await,
+                Statement::Rpc(sql, params) => lease.client().rpc_query_cached_into(sql, params, &mut writer).await,
+            };
+            match outcome {
+                Ok(()) => {
+                    release_lease(conn_id, lease);
+                    Ok(())
+                }
+                Err(e) => {
+                    discard_lease(lease);
+                    Err(to_pyerr(e))
+                }
+            }
+        })
+    });
+
+    let rx = Arc::new(Mutex::new(rx));
+    let cols = loop {
+        let rx = rx.clone();
+        let frame = tokio::task::spawn_blocking(move || rx.lock().recv().ok())
+            .await
+            .expect("cursor receiver thread panicked");
+        match frame {
+            Some(StreamFrame::Metadata(cols)) if !cols.is_empty() => break cols,
+            Some(StreamFrame::Metadata(_)) | Some(StreamFrame::Info { .. }) | Some(StreamFrame::Row(_)) => continue,
+            None | Some(StreamFrame::End) => {
+                if let Ok(Err(e)) = task.await {
+                    return Err(e);
+                }
+                return Err(pyo3::exceptions::PyRuntimeError::new_err("Query returned no result set"));
+            }
+        }
+    };
+    let rx = Arc::try_unwrap(rx).ok().expect("no other cursor holds this receiver yet").into_inner();
+
+    let cursor_id = NEXT_CURSOR_ID.fetch_add(1, Ordering::Relaxed);
+    BRIDGE.cursors.lock().insert(cursor_id, Cursor::new(rx, task, cols));
+    Ok(cursor_id)
+}
+
+/// Start a streaming query: acquire a client, then hand its batch/RPC call
+/// off to a dedicated blocking-pool thread so `StreamingRowWriter`'s
+/// channel sends can apply real backpressure without stalling `BRIDGE.rt`'s
+/// single async worker thread. Returns as soon as that thread is spawned -
+/// the first frame isn't awaited until `do_stream_next`.
+///
+/// Retries once on the same transient-failure classification `run_batch`
+/// uses, same as `run_batch` and `do_query_msgpack` (`do_query_cursor` does
+/// not retry) - but only while the writer
+/// `!has_started()`, since a streaming query is the one path where a retry
+/// could otherwise re-send a result set the consumer is already partway
+/// through reading.
+async fn do_query_stream(conn_id: u64, pool: Arc<Pool>, stmt: Statement) -> Result<u64, PyErr> {
+    let (mut writer, rx) = StreamingRowWriter::new();
+
+    let task = tokio::task::spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(async move {
+            let mut retried = false;
+            loop {
+                let mut lease = acquire_for(conn_id, &pool).await?;
+                let outcome = match &stmt {
+                    Statement::Literal(sql) => lease.client().batch_into(sql, &mut writer).await,
+                    Statement::Rpc(sql, params) => lease.client().rpc_query_cached_into(sql, params, &mut writer).await,
+                };
+                match outcome {
+                    Ok(()) => {
+                        writer.finish();
+                        release_lease(conn_id, lease);
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        let pinned = lease.is_pinned();
+                        discard_lease(lease);
+                        if !retried && !pinned && !writer.has_started() && is_transient(&e) {
+                            retried = true;
+                            continue;
+                        }
+                        return Err(to_pyerr(e));
+                    }
+                }
+            }
+        })
+    });
+
+    let stream_id = NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed);
+    BRIDGE.streams.lock().insert(stream_id, StreamCursor::new(rx, task));
+    Ok(stream_id)
+}
+
+/// Pull the next frame of a streaming query, or `None` once it's exhausted
+/// (in which case the stream is already dropped from the registry - no
+/// need for a separate close call, though one is still safe to make).
+async fn do_stream_next(stream_id: u64) -> Result<Option<StreamFrame>, PyErr> {
+    let mut cursor = {
+        let mut streams = BRIDGE.streams.lock();
+        streams
+            .remove(&stream_id)
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Stream is closed"))?
+    };
+    let frame = cursor.next_frame().await?;
+    if frame.is_some() {
+        BRIDGE.streams.lock().insert(stream_id, cursor);
+    }
+    Ok(frame)
+}
+
+async fn do_stream_close(stream_id: u64) -> Result<(), PyErr> {
+    BRIDGE.streams.lock().remove(&stream_id);
+    Ok(())
+}
+
+async fn do_fetchmany(cursor_id: u64, n: usize) -> Result<QueryResult, PyErr> {
+    let mut cursor = {
+        let mut cursors = BRIDGE.cursors.lock();
+        cursors
+            .remove(&cursor_id)
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Cursor is closed"))?
+    };
+    let result = cursor.fetchmany(n).await;
+    BRIDGE.cursors.lock().insert(cursor_id, cursor);
+    result
+}
+
+async fn do_close_cursor(cursor_id: u64) -> Result<(), PyErr> {
+    BRIDGE.cursors.lock().remove(&cursor_id);
+    Ok(())
+}
+
+async fn do_execute(conn_id: u64, pool: Arc<Pool>, stmt: Statement) -> Result<String, PyErr> {
+    let trimmed = stmt.sql().trim().to_uppercase();
     let needs_rowcount = trimmed.starts_with("INSERT ")
         || trimmed.starts_with("UPDATE ")
         || trimmed.starts_with("DELETE ")
         || trimmed.starts_with("MERGE ");
 
-    let batch_sql = if needs_rowcount {
-        format!("{}\nSELECT @@ROWCOUNT AS __rc__", sql)
+    let stmt = if needs_rowcount {
+        stmt.append("SELECT @@ROWCOUNT AS __rc__")
     } else {
-        sql
+        stmt
     };
 
-    let mut c = conn.lock().await;
-    let mut msw = MultiSetWriter::new();
-    c.batch_into(&batch_sql, &mut msw).await.map_err(to_pyerr)?;
-    drop(c);
-    let sets = msw.finalize();
+    let msw = run_batch(conn_id, &pool, &stmt).await?;
+    let mut sets = msw.finalize();
 
     let mut rowcount = 0i64;
-    for (cols, writer) in &sets {
+    for (cols, writer) in &mut sets {
         if cols.len() == 1 && cols[0].name == "__rc__" && writer.row_count() > 0 {
             if let CompactValue::I64(v) = writer.get(0, 0) {
-                rowcount = *v;
+                rowcount = v;
             }
         }
     }
@@ -179,26 +459,64 @@ async fn do_execute(id: u64, sql: String) -> Result<String, PyErr> {
     }
 }
 
-async fn do_execute_raw(id: u64, sql: String) -> Result<(), PyErr> {
-    let conn = get_conn(id)?;
-    let mut c = conn.lock().await;
-    let mut msw = MultiSetWriter::new();
-    c.batch_into(&sql, &mut msw).await.map_err(to_pyerr)?;
+async fn do_execute_raw(conn_id: u64, pool: Arc<Pool>, sql: String) -> Result<(), PyErr> {
+    run_batch(conn_id, &pool, &Statement::Literal(sql)).await?;
     Ok(())
 }
 
-async fn do_execute_many(id: u64, sqls: Vec<String>) -> Result<(), PyErr> {
-    let conn = get_conn(id)?;
-    for sql in sqls {
-        let mut c = conn.lock().await;
-        let mut msw = MultiSetWriter::new();
-        c.batch_into(&sql, &mut msw).await.map_err(to_pyerr)?;
+async fn do_execute_many(conn_id: u64, pool: Arc<Pool>, stmts: Vec<Statement>) -> Result<(), PyErr> {
+    // Each statement gets its own transparent-reconnect attempt; a mid-loop
+    // reconnect does lose any session state (temp tables, declared
+    // variables) the earlier statements in this batch had built up.
+    for stmt in stmts {
+        run_batch(conn_id, &pool, &stmt).await?;
     }
     Ok(())
 }
 
 async fn do_close(id: u64) -> Result<(), PyErr> {
     BRIDGE.connections.lock().remove(&id);
+    // A pinned session on a closed connection would otherwise hold its
+    // permit forever - nothing left to `native_unpin_session` it.
+    BRIDGE.pinned.lock().remove(&id);
+    Ok(())
+}
+
+/// Check a client out of `conn_id`'s pool and hold it in the `Bridge` for
+/// every ordinary execute-family call (and `do_query_cursor`/
+/// `do_query_stream`) on this `conn_id` until `native_unpin_session`, the
+/// way a single `Mutex<TdsClient>` used to unconditionally for everything -
+/// so a `BEGIN TRAN` / query / `COMMIT TRAN` split across separate calls
+/// stays on one physical session instead of whichever one the pool happens
+/// to hand out next.
+async fn do_pin_session(conn_id: u64) -> Result<(), PyErr> {
+    let pool = get_conn(conn_id)?;
+    {
+        let mut pinned = BRIDGE.pinned.lock();
+        if pinned.contains_key(&conn_id) {
+            return Err(pyo3::exceptions::PyRuntimeError::new_err(
+                "Connection is already pinned",
+            ));
+        }
+        // Claim the slot before the `await` below so a concurrent pin
+        // attempt on the same `conn_id` sees it taken instead of also
+        // passing the `contains_key` check above.
+        pinned.insert(conn_id, PinSlot::Pinning);
+    }
+    match pool.acquire().await {
+        Ok(client) => {
+            BRIDGE.pinned.lock().insert(conn_id, PinSlot::Ready(client));
+            Ok(())
+        }
+        Err(e) => {
+            BRIDGE.pinned.lock().remove(&conn_id);
+            Err(e)
+        }
+    }
+}
+
+async fn do_unpin_session(conn_id: u64) -> Result<(), PyErr> {
+    BRIDGE.pinned.lock().remove(&conn_id);
     Ok(())
 }
 
@@ -288,6 +606,34 @@ fn convert_query_result(py: Python<'_>, result: QueryResult) -> PyResult<PyObjec
     }
 }
 
+/// A streamed frame as `("metadata", col_names)`, `("row", values)`, or
+/// `("info", number, message)`, or `None` once the stream is exhausted.
+/// `StreamFrame::End` never reaches here - `StreamCursor::next_frame`
+/// turns it into `None` itself.
+fn convert_stream_frame(py: Python<'_>, frame: Option<StreamFrame>) -> PyResult<PyObject> {
+    match frame {
+        None => Ok(py.None()),
+        Some(StreamFrame::Metadata(cols)) => {
+            let col_names: Vec<String> = cols.iter().map(|c| c.name.clone()).collect();
+            let result = ("metadata", col_names);
+            Ok(result.into_pyobject(py)?.into_any().unbind())
+        }
+        Some(StreamFrame::Row(values)) => {
+            let mut py_values: Vec<PyObject> = Vec::with_capacity(values.len());
+            for v in &values {
+                py_values.push(compact_value_to_py(py, v)?);
+            }
+            let result = ("row", py_values);
+            Ok(result.into_pyobject(py)?.into_any().unbind())
+        }
+        Some(StreamFrame::Info { number, message }) => {
+            let result = ("info", number, message);
+            Ok(result.into_pyobject(py)?.into_any().unbind())
+        }
+        Some(StreamFrame::End) => unreachable!("StreamCursor::next_frame turns End into None"),
+    }
+}
+
 fn convert_string(py: Python<'_>, s: String) -> PyResult<PyObject> {
     Ok(s.into_pyobject(py)?.into_any().unbind())
 }
@@ -296,6 +642,10 @@ fn convert_unit(py: Python<'_>, _: ()) -> PyResult<PyObject> {
     Ok(py.None())
 }
 
+fn convert_bytes(py: Python<'_>, bytes: Vec<u8>) -> PyResult<PyObject> {
+    Ok(pyo3::types::PyBytes::new(py, &bytes).into_any().unbind())
+}
+
 #[pyfunction]
 fn native_connect<'py>(py: Python<'py>, dsn: String) -> PyResult<Bound<'py, PyAny>> {
     spawn_future(py, do_connect(dsn), convert_id)
@@ -308,12 +658,23 @@ fn native_query<'py>(
     sql: String,
     params: Vec<PyObject>,
 ) -> PyResult<Bound<'py, PyAny>> {
-    let final_sql = if params.is_empty() {
-        sql
-    } else {
-        substitute_params(py, &sql, &params)?
-    };
-    spawn_future(py, do_query(conn_id, final_sql), convert_query_result)
+    let pool = get_conn(conn_id)?;
+    let stmt = build_statement(py, &pool, sql, &params)?;
+    spawn_future(py, do_query(conn_id, pool, stmt), convert_query_result)
+}
+
+/// Like `native_query`, but the result set comes back as a MessagePack byte
+/// string instead of a Python tuple of values - see `MsgPackRowWriter`.
+#[pyfunction]
+fn native_query_msgpack<'py>(
+    py: Python<'py>,
+    conn_id: u64,
+    sql: String,
+    params: Vec<PyObject>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let pool = get_conn(conn_id)?;
+    let stmt = build_statement(py, &pool, sql, &params)?;
+    spawn_future(py, do_query_msgpack(conn_id, pool, stmt), convert_bytes)
 }
 
 #[pyfunction]
@@ -323,12 +684,9 @@ fn native_execute<'py>(
     sql: String,
     params: Vec<PyObject>,
 ) -> PyResult<Bound<'py, PyAny>> {
-    let final_sql = if params.is_empty() {
-        sql
-    } else {
-        substitute_params(py, &sql, &params)?
-    };
-    spawn_future(py, do_execute(conn_id, final_sql), convert_string)
+    let pool = get_conn(conn_id)?;
+    let stmt = build_statement(py, &pool, sql, &params)?;
+    spawn_future(py, do_execute(conn_id, pool, stmt), convert_string)
 }
 
 #[pyfunction]
@@ -337,7 +695,8 @@ fn native_execute_raw<'py>(
     conn_id: u64,
     sql: String,
 ) -> PyResult<Bound<'py, PyAny>> {
-    spawn_future(py, do_execute_raw(conn_id, sql), convert_unit)
+    let pool = get_conn(conn_id)?;
+    spawn_future(py, do_execute_raw(conn_id, pool, sql), convert_unit)
 }
 
 #[pyfunction]
@@ -347,17 +706,12 @@ fn native_execute_many<'py>(
     sql: String,
     args_list: Vec<Vec<PyObject>>,
 ) -> PyResult<Bound<'py, PyAny>> {
-    let sqls: Vec<String> = args_list
+    let pool = get_conn(conn_id)?;
+    let stmts: Vec<Statement> = args_list
         .iter()
-        .map(|params| {
-            if params.is_empty() {
-                Ok(sql.clone())
-            } else {
-                Python::with_gil(|py| substitute_params(py, &sql, params))
-            }
-        })
+        .map(|params| build_statement(py, &pool, sql.clone(), params))
         .collect::<PyResult<_>>()?;
-    spawn_future(py, do_execute_many(conn_id, sqls), convert_unit)
+    spawn_future(py, do_execute_many(conn_id, pool, stmts), convert_unit)
 }
 
 #[pyfunction]
@@ -365,13 +719,87 @@ fn native_close<'py>(py: Python<'py>, conn_id: u64) -> PyResult<Bound<'py, PyAny
     spawn_future(py, do_close(conn_id), convert_unit)
 }
 
+/// Pin `conn_id` to one physical session: every ordinary execute-family
+/// call (and `native_query_cursor`/`native_query_stream`) on this `conn_id`
+/// reuses it instead of checking a fresh client out of the pool, until
+/// `native_unpin_session` lets it go. Errors if `conn_id` is already pinned.
+#[pyfunction]
+fn native_pin_session<'py>(py: Python<'py>, conn_id: u64) -> PyResult<Bound<'py, PyAny>> {
+    spawn_future(py, do_pin_session(conn_id), convert_unit)
+}
+
+/// Release a client `native_pin_session` parked for `conn_id`, if any,
+/// returning it to the pool like any other checked-in client. A no-op if
+/// `conn_id` isn't pinned.
+#[pyfunction]
+fn native_unpin_session<'py>(py: Python<'py>, conn_id: u64) -> PyResult<Bound<'py, PyAny>> {
+    spawn_future(py, do_unpin_session(conn_id), convert_unit)
+}
+
+#[pyfunction]
+fn native_query_cursor<'py>(
+    py: Python<'py>,
+    conn_id: u64,
+    sql: String,
+    params: Vec<PyObject>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let pool = get_conn(conn_id)?;
+    let stmt = build_statement(py, &pool, sql, &params)?;
+    spawn_future(py, do_query_cursor(conn_id, pool, stmt), convert_id)
+}
+
+#[pyfunction]
+fn native_fetchmany<'py>(py: Python<'py>, cursor_id: u64, n: usize) -> PyResult<Bound<'py, PyAny>> {
+    spawn_future(py, do_fetchmany(cursor_id, n), convert_query_result)
+}
+
+#[pyfunction]
+fn native_close_cursor<'py>(py: Python<'py>, cursor_id: u64) -> PyResult<Bound<'py, PyAny>> {
+    spawn_future(py, do_close_cursor(cursor_id), convert_unit)
+}
+
+/// Start a query in streaming/iterator mode: rows are handed back one frame
+/// at a time via `native_stream_next` as they come off the wire, instead of
+/// the whole result set materializing before this call even returns.
+#[pyfunction]
+fn native_query_stream<'py>(
+    py: Python<'py>,
+    conn_id: u64,
+    sql: String,
+    params: Vec<PyObject>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let pool = get_conn(conn_id)?;
+    let stmt = build_statement(py, &pool, sql, &params)?;
+    spawn_future(py, do_query_stream(conn_id, pool, stmt), convert_id)
+}
+
+#[pyfunction]
+fn native_stream_next<'py>(py: Python<'py>, stream_id: u64) -> PyResult<Bound<'py, PyAny>> {
+    spawn_future(py, do_stream_next(stream_id), convert_stream_frame)
+}
+
+#[pyfunction]
+fn native_stream_close<'py>(py: Python<'py>, stream_id: u64) -> PyResult<Bound<'py, PyAny>> {
+    spawn_future(py, do_stream_close(stream_id), convert_unit)
+}
+
 #[pymodule]
 fn hiss_native(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(native_connect, m)?)?;
     m.add_function(wrap_pyfunction!(native_query, m)?)?;
+    m.add_function(wrap_pyfunction!(native_query_msgpack, m)?)?;
     m.add_function(wrap_pyfunction!(native_execute, m)?)?;
     m.add_function(wrap_pyfunction!(native_execute_raw, m)?)?;
     m.add_function(wrap_pyfunction!(native_execute_many, m)?)?;
     m.add_function(wrap_pyfunction!(native_close, m)?)?;
+    m.add_function(wrap_pyfunction!(native_pin_session, m)?)?;
+    m.add_function(wrap_pyfunction!(native_unpin_session, m)?)?;
+    m.add_function(wrap_pyfunction!(native_query_cursor, m)?)?;
+    m.add_function(wrap_pyfunction!(native_fetchmany, m)?)?;
+    m.add_function(wrap_pyfunction!(native_close_cursor, m)?)?;
+    m.add_function(wrap_pyfunction!(native_query_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(native_stream_next, m)?)?;
+    m.add_function(wrap_pyfunction!(native_stream_close, m)?)?;
+    register_exceptions(m)?;
     Ok(())
 }
@@ -0,0 +1,335 @@
+use tabby::RowWriter;
+
+/// Ext type tags for the SQL types MessagePack has no native representation
+/// for. Stable across versions of this crate since callers may persist the
+/// encoded bytes (that's the whole point of this writer).
+const EXT_DATE: i8 = 1;
+const EXT_TIME: i8 = 2;
+const EXT_DATETIME: i8 = 3;
+const EXT_DATETIMEOFFSET: i8 = 4;
+const EXT_DECIMAL: i8 = 5;
+const EXT_GUID: i8 = 6;
+
+fn write_array_header(out: &mut Vec<u8>, len: usize) {
+    if len <= 15 {
+        out.push(0x90 | len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(0xdc);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdd);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn write_map_header(out: &mut Vec<u8>, len: usize) {
+    if len <= 15 {
+        out.push(0x80 | len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(0xde);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdf);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    let bytes = s.as_bytes();
+    let len = bytes.len();
+    if len <= 31 {
+        out.push(0xa0 | len as u8);
+    } else if len <= u8::MAX as usize {
+        out.push(0xd9);
+        out.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(0xda);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdb);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(bytes);
+}
+
+fn write_bin(out: &mut Vec<u8>, bytes: &[u8]) {
+    let len = bytes.len();
+    if len <= u8::MAX as usize {
+        out.push(0xc4);
+        out.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(0xc5);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xc6);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(bytes);
+}
+
+/// Smallest marker for `val` by magnitude: positive/negative fixint first,
+/// then `int 8/16/32/64`, same ladder `rmp` climbs for an `EfficientInt`.
+fn write_int(out: &mut Vec<u8>, val: i64) {
+    if (0..=127).contains(&val) {
+        out.push(val as u8);
+    } else if (-32..=-1).contains(&val) {
+        out.push(val as i8 as u8);
+    } else if (i8::MIN as i64..=i8::MAX as i64).contains(&val) {
+        out.push(0xd0);
+        out.push(val as i8 as u8);
+    } else if (i16::MIN as i64..=i16::MAX as i64).contains(&val) {
+        out.push(0xd1);
+        out.extend_from_slice(&(val as i16).to_be_bytes());
+    } else if (i32::MIN as i64..=i32::MAX as i64).contains(&val) {
+        out.push(0xd2);
+        out.extend_from_slice(&(val as i32).to_be_bytes());
+    } else {
+        out.push(0xd3);
+        out.extend_from_slice(&val.to_be_bytes());
+    }
+}
+
+fn write_f64(out: &mut Vec<u8>, val: f64) {
+    out.push(0xcb);
+    out.extend_from_slice(&val.to_be_bytes());
+}
+
+fn write_nil(out: &mut Vec<u8>) {
+    out.push(0xc0);
+}
+
+fn write_bool(out: &mut Vec<u8>, val: bool) {
+    out.push(if val { 0xc3 } else { 0xc2 });
+}
+
+fn write_ext(out: &mut Vec<u8>, tag: i8, data: &[u8]) {
+    match data.len() {
+        1 => out.push(0xd4),
+        2 => out.push(0xd5),
+        4 => out.push(0xd6),
+        8 => out.push(0xd7),
+        16 => out.push(0xd8),
+        len if len <= u8::MAX as usize => {
+            out.push(0xc7);
+            out.push(len as u8);
+        }
+        len if len <= u16::MAX as usize => {
+            out.push(0xc8);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(0xc9);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    out.push(tag as u8);
+    out.extend_from_slice(data);
+}
+
+/// A `RowWriter` that serializes straight to MessagePack bytes instead of
+/// building `CompactValue`s in memory, for callers that want to cache query
+/// output, ship it over IPC, or hand it to a non-Python consumer.
+///
+/// Each result set is framed as a header map (`{"columns": [...]}`) followed
+/// by an array of rows, each row an array of values using the most compact
+/// marker available. SQL's temporal/exotic types ride in ext types (the
+/// `EXT_*` tags above) since MessagePack has no native representation for
+/// them.
+pub struct MsgPackRowWriter {
+    out: Vec<u8>,
+    current_cols: Option<Vec<String>>,
+    col_count: usize,
+    cell_in_row: usize,
+    row_count: usize,
+    rows: Vec<u8>,
+}
+
+impl MsgPackRowWriter {
+    pub fn new() -> Self {
+        Self { out: Vec::new(), current_cols: None, col_count: 0, cell_in_row: 0, row_count: 0, rows: Vec::new() }
+    }
+
+    /// Emit the buffered header + row array for the in-progress result set
+    /// (if any) into `out`, then reset for whatever comes next.
+    fn flush_current_set(&mut self) {
+        if let Some(cols) = self.current_cols.take() {
+            write_map_header(&mut self.out, 1);
+            write_str(&mut self.out, "columns");
+            write_array_header(&mut self.out, cols.len());
+            for c in &cols {
+                write_str(&mut self.out, c);
+            }
+            write_array_header(&mut self.out, self.row_count);
+            self.out.extend_from_slice(&self.rows);
+        }
+        self.rows.clear();
+        self.row_count = 0;
+        self.cell_in_row = 0;
+        self.col_count = 0;
+    }
+
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.flush_current_set();
+        self.out
+    }
+
+    /// Each row's array length (`col_count`) is known up front from
+    /// `on_metadata`, so its header can go out before the first cell instead
+    /// of waiting on a row count we won't have until `on_row_done`.
+    #[inline]
+    fn begin_cell(&mut self) {
+        if self.cell_in_row == 0 {
+            write_array_header(&mut self.rows, self.col_count);
+        }
+        self.cell_in_row += 1;
+    }
+}
+
+impl RowWriter for MsgPackRowWriter {
+    fn on_metadata(&mut self, columns: &[tabby::Column]) {
+        self.flush_current_set();
+        self.col_count = columns.len();
+        self.current_cols = Some(columns.iter().map(|c| c.name().to_string()).collect());
+    }
+
+    fn on_row_done(&mut self) {
+        self.cell_in_row = 0;
+        self.row_count += 1;
+    }
+
+    fn on_info(&mut self, _number: u32, _message: &str) {}
+
+    #[inline] fn write_null(&mut self, _col: usize) { self.begin_cell(); write_nil(&mut self.rows); }
+    #[inline] fn write_bool(&mut self, _col: usize, val: bool) { self.begin_cell(); write_bool(&mut self.rows, val); }
+    #[inline] fn write_u8(&mut self, _col: usize, val: u8) { self.begin_cell(); write_int(&mut self.rows, val as i64); }
+    #[inline] fn write_i16(&mut self, _col: usize, val: i16) { self.begin_cell(); write_int(&mut self.rows, val as i64); }
+    #[inline] fn write_i32(&mut self, _col: usize, val: i32) { self.begin_cell(); write_int(&mut self.rows, val as i64); }
+    #[inline] fn write_i64(&mut self, _col: usize, val: i64) { self.begin_cell(); write_int(&mut self.rows, val); }
+    #[inline] fn write_f32(&mut self, _col: usize, val: f32) { self.begin_cell(); write_f64(&mut self.rows, val as f64); }
+    #[inline] fn write_f64(&mut self, _col: usize, val: f64) { self.begin_cell(); write_f64(&mut self.rows, val); }
+    #[inline] fn write_str(&mut self, _col: usize, val: &str) { self.begin_cell(); write_str(&mut self.rows, val); }
+    #[inline] fn write_bytes(&mut self, _col: usize, val: &[u8]) { self.begin_cell(); write_bin(&mut self.rows, val); }
+    #[inline] fn write_date(&mut self, _col: usize, days: i32) { self.begin_cell(); write_ext(&mut self.rows, EXT_DATE, &days.to_be_bytes()); }
+    #[inline] fn write_time(&mut self, _col: usize, nanos: i64) { self.begin_cell(); write_ext(&mut self.rows, EXT_TIME, &nanos.to_be_bytes()); }
+    #[inline] fn write_datetime(&mut self, _col: usize, micros: i64) { self.begin_cell(); write_ext(&mut self.rows, EXT_DATETIME, &micros.to_be_bytes()); }
+    #[inline]
+    fn write_datetimeoffset(&mut self, _col: usize, micros: i64, offset_minutes: i16) {
+        self.begin_cell();
+        let mut data = [0u8; 10];
+        data[..8].copy_from_slice(&micros.to_be_bytes());
+        data[8..].copy_from_slice(&offset_minutes.to_be_bytes());
+        write_ext(&mut self.rows, EXT_DATETIMEOFFSET, &data);
+    }
+    #[inline]
+    fn write_decimal(&mut self, _col: usize, value: i128, precision: u8, scale: u8) {
+        self.begin_cell();
+        let mut data = [0u8; 18];
+        data[..16].copy_from_slice(&value.to_be_bytes());
+        data[16] = precision;
+        data[17] = scale;
+        write_ext(&mut self.rows, EXT_DECIMAL, &data);
+    }
+    #[inline] fn write_guid(&mut self, _col: usize, bytes: &[u8; 16]) { self.begin_cell(); write_ext(&mut self.rows, EXT_GUID, bytes); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_picks_smallest_marker_by_magnitude() {
+        let mut out = Vec::new();
+        write_int(&mut out, 0);
+        assert_eq!(out, vec![0x00]);
+
+        out.clear();
+        write_int(&mut out, 127);
+        assert_eq!(out, vec![0x7f]);
+
+        out.clear();
+        write_int(&mut out, -1);
+        assert_eq!(out, vec![0xff]);
+
+        out.clear();
+        write_int(&mut out, -32);
+        assert_eq!(out, vec![0xe0]);
+
+        out.clear();
+        write_int(&mut out, 128);
+        assert_eq!(out, vec![0xd1, 0x00, 0x80]);
+
+        out.clear();
+        write_int(&mut out, i64::MAX);
+        assert_eq!(out[0], 0xd3);
+        assert_eq!(&out[1..], &i64::MAX.to_be_bytes());
+    }
+
+    #[test]
+    fn str_picks_fixstr_for_short_strings() {
+        let mut out = Vec::new();
+        write_str(&mut out, "hi");
+        assert_eq!(out, vec![0xa2, b'h', b'i']);
+    }
+
+    #[test]
+    fn str_picks_str8_past_fixstr_range() {
+        let s = "a".repeat(32);
+        let mut out = Vec::new();
+        write_str(&mut out, &s);
+        assert_eq!(out[0], 0xd9);
+        assert_eq!(out[1], 32);
+    }
+
+    #[test]
+    fn bin_picks_smallest_bin_marker() {
+        let mut out = Vec::new();
+        write_bin(&mut out, &[1, 2, 3]);
+        assert_eq!(out[0], 0xc4);
+        assert_eq!(out[1], 3);
+    }
+
+    #[test]
+    fn ext_uses_fixext_markers_for_standard_lengths() {
+        let mut out = Vec::new();
+        write_ext(&mut out, EXT_GUID, &[0u8; 16]);
+        assert_eq!(out[0], 0xd8); // fixext16
+        assert_eq!(out[1], EXT_GUID as u8);
+    }
+
+    #[test]
+    fn array_header_switches_from_fixarray_at_16() {
+        let mut out = Vec::new();
+        write_array_header(&mut out, 15);
+        assert_eq!(out, vec![0x9f]);
+
+        out.clear();
+        write_array_header(&mut out, 16);
+        assert_eq!(out, vec![0xdc, 0x00, 0x10]);
+    }
+
+    #[test]
+    fn map_header_switches_from_fixmap_at_16() {
+        let mut out = Vec::new();
+        write_map_header(&mut out, 15);
+        assert_eq!(out, vec![0x8f]);
+
+        out.clear();
+        write_map_header(&mut out, 16);
+        assert_eq!(out, vec![0xde, 0x00, 0x10]);
+    }
+
+    #[test]
+    fn ext_uses_variable_length_marker_past_fixext16() {
+        let mut out = Vec::new();
+        write_ext(&mut out, EXT_DECIMAL, &[0u8; 18]);
+        assert_eq!(out[0], 0xc7); // ext8
+        assert_eq!(out[1], 18);
+        assert_eq!(out[2], EXT_DECIMAL as u8);
+    }
+
+    #[test]
+    fn finalize_on_empty_writer_emits_nothing() {
+        let w = MsgPackRowWriter::new();
+        assert!(w.finalize().is_empty());
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use pyo3::prelude::*;
+use tabby::{AuthMethod, Client, ColumnData, Config, EncryptionLevel, RowWriter};
+use tokio::net::TcpStream;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::dsn::ConnectionOptions;
+use crate::row_writer::MultiSetWriter;
+use crate::stmt_cache::StmtCache;
+
+pub type TdsClient = Client<Compat<TcpStream>>;
+
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sizing knobs for a `Pool`, read from `Max Pool Size` / `Min Pool Size` /
+/// `Connection Lifetime` DSN keys.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolConfig {
+    pub min_size: u32,
+    pub max_size: u32,
+    pub connection_lifetime: Option<Duration>,
+}
+
+struct Idle {
+    client: TdsClient,
+    created_at: Instant,
+    stmt_cache: StmtCache,
+    /// Held for as long as this physical session exists, idle or not, so
+    /// `max_size` caps total live sessions rather than just concurrent
+    /// checkouts - see `Pool::acquire`/`release`.
+    permit: OwnedSemaphorePermit,
+}
+
+/// A connect-time failure, kept distinct from `tabby::error::Error` so we can
+/// tell a dropped-socket retry candidate apart from a permanent rejection
+/// (bad credentials, unknown database) before it gets wrapped into a `PyErr`.
+enum ConnectError {
+    Tcp(std::io::Error),
+    Tds(tabby::error::Error),
+}
+
+impl ConnectError {
+    /// Transient failures (refused/reset/aborted TCP connects, pre-login
+    /// handshake IO errors) are worth retrying; a server-rejected login
+    /// (bad credentials, unknown database) is not.
+    fn is_transient(&self) -> bool {
+        match self {
+            ConnectError::Tcp(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::TimedOut
+            ),
+            ConnectError::Tds(tabby::error::Error::Server(token)) => {
+                !matches!(token.code(), 18456 /* login failed */ | 4060 /* database does not exist */)
+            }
+            ConnectError::Tds(_) => true,
+        }
+    }
+
+    fn into_pyerr(self) -> PyErr {
+        match self {
+            ConnectError::Tcp(e) => {
+                pyo3::exceptions::PyConnectionError::new_err(format!("TCP connect failed: {}", e))
+            }
+            ConnectError::Tds(e) => {
+                pyo3::exceptions::PyConnectionError::new_err(format!("TDS connect failed: {}", e))
+            }
+        }
+    }
+}
+
+async fn connect_client_once(opts: &ConnectionOptions) -> Result<TdsClient, ConnectError> {
+    let mut config = Config::new();
+    config.host(&opts.host);
+    config.port(opts.port);
+    config.database(&opts.database);
+    config.authentication(AuthMethod::sql_server(&opts.uid, &opts.pwd));
+    if opts.trust_cert {
+        config.trust_cert();
+    }
+    config.encryption(EncryptionLevel::Required);
+
+    let tcp = TcpStream::connect(config.get_addr())
+        .await
+        .map_err(ConnectError::Tcp)?;
+    tcp.set_nodelay(true).map_err(ConnectError::Tcp)?;
+    Client::connect(config, tcp.compat_write())
+        .await
+        .map_err(ConnectError::Tds)
+}
+
+/// Backoff delay before retry attempt `attempt` (0-based): the DSN's
+/// `ConnectRetryInterval` doubled once per attempt, for at most six doublings,
+/// capped at a minute, with a little jitter knocked off the top so a
+/// thundering herd of reconnects doesn't land in lockstep.
+fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let exp = base.as_millis().saturating_mul(1u128 << attempt.min(6));
+    let capped = exp.min(60_000) as u64;
+    let jitter_seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter = jitter_seed % (capped / 4 + 1);
+    Duration::from_millis(capped.saturating_sub(jitter))
+}
+
+/// Dial a fresh TDS session, retrying transient failures with exponential
+/// backoff per the DSN's `ConnectRetryCount`/`ConnectRetryInterval`. Shared
+/// by `Pool::new`/`acquire`/health-check so every path that grows the pool
+/// gets the same retry behavior `do_connect` used to apply just once.
+pub async fn connect_client(opts: &ConnectionOptions) -> Result<TdsClient, PyErr> {
+    let mut attempt = 0;
+    loop {
+        match connect_client_once(opts).await {
+            Ok(client) => return Ok(client),
+            Err(e) if attempt < opts.connect_retry_count && e.is_transient() => {
+                tokio::time::sleep(backoff_delay(opts.connect_retry_interval, attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into_pyerr()),
+        }
+    }
+}
+
+/// A small pool of TDS sessions backing one logical `native_connect` id.
+///
+/// Modeled on mysql_async's `Pool`: `acquire` checks a client out for the
+/// duration of one batch and the guard returns it to the idle queue on drop,
+/// so concurrent `await`ed queries against the same logical connection no
+/// longer serialize on a single `Mutex<TdsClient>`.
+pub struct Pool {
+    opts: ConnectionOptions,
+    config: PoolConfig,
+    idle: Mutex<VecDeque<Idle>>,
+    semaphore: Arc<Semaphore>,
+}
+
+impl Pool {
+    pub fn uses_legacy_literal_params(&self) -> bool {
+        self.opts.legacy_literal_params
+    }
+
+    pub fn statement_cache_size(&self) -> u32 {
+        self.opts.statement_cache_size
+    }
+
+    pub fn spill_threshold_bytes(&self) -> u32 {
+        self.opts.spill_threshold_bytes
+    }
+
+    pub async fn new(opts: ConnectionOptions, config: PoolConfig) -> Result<Arc<Self>, PyErr> {
+        let pool = Self {
+            opts,
+            config,
+            idle: Mutex::new(VecDeque::new()),
+            semaphore: Arc::new(Semaphore::new(config.max_size.max(1) as usize)),
+        };
+        for _ in 0..config.min_size {
+            let permit = pool.semaphore.clone().acquire_owned().await.expect("pool semaphore is never closed");
+            let client = connect_client(&pool.opts).await?;
+            let stmt_cache = StmtCache::new(pool.statement_cache_size() as usize);
+            pool.idle.lock().push_back(Idle { client, created_at: Instant::now(), stmt_cache, permit });
+        }
+        Ok(Arc::new(pool))
+    }
+
+    /// An idle entry already carries the permit for its slot, so reusing one
+    /// needs no semaphore call at all; only minting a brand new physical
+    /// session has to wait for (and hold) a fresh permit, which is what
+    /// actually caps total live sessions at `max_size` - idle or checked out.
+    pub async fn acquire(self: &Arc<Self>) -> Result<PooledClient, PyErr> {
+        loop {
+            let next = self.idle.lock().pop_front();
+            match next {
+                Some(entry) => {
+                    if let Some(lifetime) = self.config.connection_lifetime {
+                        if entry.created_at.elapsed() > lifetime {
+                            continue; // expired; entry (client + permit) dropped, try the next idle client
+                        }
+                    }
+                    return Ok(PooledClient {
+                        pool: self.clone(),
+                        client: Some(entry.client),
+                        stmt_cache: entry.stmt_cache,
+                        _permit: Some(entry.permit),
+                    });
+                }
+                None => {
+                    let permit = tokio::time::timeout(ACQUIRE_TIMEOUT, self.semaphore.clone().acquire_owned())
+                        .await
+                        .map_err(|_| {
+                            pyo3::exceptions::PyTimeoutError::new_err("timed out waiting for a pooled connection")
+                        })?
+                        .expect("pool semaphore is never closed");
+                    let client = connect_client(&self.opts).await?;
+                    return Ok(PooledClient {
+                        pool: self.clone(),
+                        client: Some(client),
+                        stmt_cache: StmtCache::new(self.statement_cache_size() as usize),
+                        _permit: Some(permit),
+                    });
+                }
+            }
+        }
+    }
+
+    fn release(&self, client: TdsClient, stmt_cache: StmtCache, permit: OwnedSemaphorePermit) {
+        self.idle.lock().push_back(Idle { client, created_at: Instant::now(), stmt_cache, permit });
+    }
+
+    /// Best-effort background reclamation: drop idle clients past their
+    /// lifetime and discard any that fail a lightweight `SELECT 1`, topping
+    /// the idle queue back up to `min_size` when nothing else is using it.
+    async fn run_health_check(&self) {
+        let mut still_alive = VecDeque::new();
+        let drained: Vec<Idle> = std::mem::take(&mut *self.idle.lock()).into_iter().collect();
+        for mut entry in drained {
+            if let Some(lifetime) = self.config.connection_lifetime {
+                if entry.created_at.elapsed() > lifetime {
+                    continue; // entry (client + permit) dropped, freeing its slot
+                }
+            }
+            let mut probe = MultiSetWriter::new();
+            if entry.client.batch_into("SELECT 1", &mut probe).await.is_ok() {
+                still_alive.push_back(entry);
+            }
+            // else entry (client + permit) dropped, freeing its slot
+        }
+        *self.idle.lock() = still_alive;
+
+        // Each top-up connection needs its own permit, same as any other
+        // live session - if every slot is already checked out, there's no
+        // room to top up until one frees up on its own.
+        while (self.idle.lock().len() as u32) < self.config.min_size {
+            let Ok(permit) = self.semaphore.clone().try_acquire_owned() else {
+                break;
+            };
+            match connect_client(&self.opts).await {
+                Ok(client) => {
+                    let stmt_cache = StmtCache::new(self.statement_cache_size() as usize);
+                    self.idle.lock().push_back(Idle { client, created_at: Instant::now(), stmt_cache, permit });
+                }
+                Err(_) => break, // permit drops here, freeing the slot back up
+            }
+        }
+    }
+
+    pub fn spawn_health_check(self: &Arc<Self>, rt: &tokio::runtime::Runtime) {
+        let weak = Arc::downgrade(self);
+        rt.spawn(async move {
+            loop {
+                tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+                let Some(pool) = weak.upgrade() else { break };
+                pool.run_health_check().await;
+            }
+        });
+    }
+}
+
+/// A `TdsClient` checked out of a `Pool`; returns itself to the idle queue on
+/// drop unless `discard` was called (e.g. after an IO error made it unsafe
+/// to reuse). Owns an `Arc<Pool>` rather than borrowing it so a checked-out
+/// client can outlive the call that acquired it - e.g. parked in the cursor
+/// registry between `fetchmany` calls.
+pub struct PooledClient {
+    pool: Arc<Pool>,
+    client: Option<TdsClient>,
+    stmt_cache: StmtCache,
+    /// `None` only ever briefly, inside `Drop::drop` once the permit's been
+    /// handed off to the `Idle` entry the client is released back as.
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl std::ops::Deref for PooledClient {
+    type Target = TdsClient;
+    fn deref(&self) -> &TdsClient {
+        self.client.as_ref().expect("client taken before drop")
+    }
+}
+
+impl std::ops::DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut TdsClient {
+        self.client.as_mut().expect("client taken before drop")
+    }
+}
+
+impl PooledClient {
+    /// Drop the underlying client instead of returning it to the pool. Its
+    /// prepared-statement cache goes with it - a new physical session
+    /// wouldn't recognize the old handles anyway.
+    pub fn discard(&mut self) {
+        self.client.take();
+    }
+
+    /// Run `params` against `sql` as an `sp_executesql`-equivalent RPC,
+    /// transparently reusing an `sp_prepare` handle from this session's
+    /// statement cache when one exists for this exact `(sql, param types)`
+    /// pair, and stashing a freshly prepared one when it doesn't. Falls back
+    /// to a one-shot RPC with no caching when `Statement Cache Size=0`.
+    ///
+    /// Generic over `RowWriter` rather than pinned to `MultiSetWriter` so
+    /// the statement cache benefits every sink, not just the buffered one -
+    /// `StreamingRowWriter` and `MsgPackRowWriter` route through here too.
+    pub async fn rpc_query_cached_into<W: RowWriter>(
+        &mut self,
+        sql: &str,
+        params: &[ColumnData<'static>],
+        writer: &mut W,
+    ) -> Result<(), tabby::error::Error> {
+        let client = self.client.as_mut().expect("client taken before drop");
+        if !self.stmt_cache.is_enabled() {
+            return client.rpc_query_into(sql, params, writer).await;
+        }
+        if let Some(handle) = self.stmt_cache.get(sql, params) {
+            return client.sp_execute_into(handle, params, writer).await;
+        }
+        let handle = client.sp_prepare(sql, params).await?;
+        let result = client.sp_execute_into(handle, params, writer).await;
+        if let Some(evicted) = self.stmt_cache.insert(sql, params, handle) {
+            let _ = client.sp_unprepare(evicted).await;
+        }
+        result
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            // `discard`ed clients never reach here with `client` still
+            // `Some`, so `_permit` is always still held at this point.
+            let permit = self._permit.take().expect("permit held until released or discarded");
+            self.pool.release(client, std::mem::take(&mut self.stmt_cache), permit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_delay_stays_within_the_doubling_attempts_cap() {
+        let base = Duration::from_secs(1);
+        // Jitter only ever shaves time off, so every attempt's delay should
+        // be at most `base * 2^attempt` (capped at 60s), and that cap should
+        // strictly grow attempt over attempt until it saturates there.
+        let mut last_cap = 0u128;
+        for attempt in 0..10u32 {
+            let cap = (base.as_millis() * (1u128 << attempt.min(6))).min(60_000);
+            let delay = backoff_delay(base, attempt).as_millis();
+            assert!(delay <= cap, "attempt {attempt}: delay {delay} exceeded cap {cap}");
+            assert!(cap >= last_cap);
+            last_cap = cap;
+        }
+        assert_eq!(last_cap, 60_000); // confirms the loop actually reached the cap
+    }
+
+    #[test]
+    fn backoff_delay_never_exceeds_one_minute_for_huge_attempts() {
+        let delay = backoff_delay(Duration::from_secs(10), u32::MAX);
+        assert!(delay.as_millis() <= 60_000);
+    }
+
+    #[test]
+    fn tcp_connect_errors_classify_retryable_kinds_as_transient() {
+        let transient = ConnectError::Tcp(std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+        assert!(transient.is_transient());
+
+        let permanent = ConnectError::Tcp(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(!permanent.is_transient());
+    }
+}
@@ -1,5 +1,7 @@
 use tabby::RowWriter;
 
+use crate::spill::SpillStore;
+
 #[derive(Clone)]
 pub enum CompactValue {
     Null,
@@ -16,37 +18,343 @@ pub enum CompactValue {
     Guid([u8; 16]),
 }
 
+/// One bit per row, packed 64 to a `u64`; a set bit means the cell is NULL.
+#[derive(Default)]
+struct NullBitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl NullBitmap {
+    fn push(&mut self, is_null: bool) {
+        if self.len % 64 == 0 {
+            self.words.push(0);
+        }
+        if is_null {
+            self.words[self.len / 64] |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    #[inline]
+    fn get(&self, row: usize) -> bool {
+        self.words[row / 64] & (1 << (row % 64)) != 0
+    }
+}
+
+/// Tightly-packed storage for one column, picked from whichever `write_*`
+/// call first lands a non-null value in it. `Unset` is an all-NULL-so-far
+/// column that hasn't committed to a type yet; `Boxed` is the fallback for
+/// a column whose writes disagree on type - shouldn't happen since every row
+/// in a result set shares one schema, but it's cheap insurance against a
+/// writer bug turning into a panic. `I8`/`I16`/`I32` only appear after
+/// `shrink_ints` has narrowed a finished `I64` column on `on_metadata`/`finalize`.
+enum ColumnBuffer {
+    Unset,
+    Bool(Vec<bool>),
+    I64(Vec<i64>),
+    I32(Vec<i32>),
+    I16(Vec<i16>),
+    I8(Vec<i8>),
+    F64(Vec<f64>),
+    Str { offsets: Vec<u32>, data: Vec<u8> },
+    Bytes { offsets: Vec<u32>, data: Vec<u8> },
+    Date(Vec<i32>),
+    Time(Vec<i64>),
+    DateTime(Vec<i64>),
+    DateTimeOffset(Vec<(i64, i16)>),
+    Decimal(Vec<(i128, u8, u8)>),
+    Guid(Vec<[u8; 16]>),
+    Boxed(Vec<CompactValue>),
+}
+
+fn boxed_get(buffer: &ColumnBuffer, nulls: &NullBitmap, row: usize) -> CompactValue {
+    if nulls.get(row) {
+        return CompactValue::Null;
+    }
+    match buffer {
+        ColumnBuffer::Unset => CompactValue::Null,
+        ColumnBuffer::Bool(v) => CompactValue::Bool(v[row]),
+        ColumnBuffer::I64(v) => CompactValue::I64(v[row]),
+        ColumnBuffer::I32(v) => CompactValue::I64(v[row] as i64),
+        ColumnBuffer::I16(v) => CompactValue::I64(v[row] as i64),
+        ColumnBuffer::I8(v) => CompactValue::I64(v[row] as i64),
+        ColumnBuffer::F64(v) => CompactValue::F64(v[row]),
+        ColumnBuffer::Str { offsets, data } => {
+            let start = if row == 0 { 0 } else { offsets[row - 1] as usize };
+            let end = offsets[row] as usize;
+            CompactValue::Str(String::from_utf8_lossy(&data[start..end]).into_owned())
+        }
+        ColumnBuffer::Bytes { offsets, data } => {
+            let start = if row == 0 { 0 } else { offsets[row - 1] as usize };
+            let end = offsets[row] as usize;
+            CompactValue::Bytes(data[start..end].to_vec())
+        }
+        ColumnBuffer::Date(v) => CompactValue::Date(v[row]),
+        ColumnBuffer::Time(v) => CompactValue::Time(v[row]),
+        ColumnBuffer::DateTime(v) => CompactValue::DateTime(v[row]),
+        ColumnBuffer::DateTimeOffset(v) => CompactValue::DateTimeOffset(v[row].0, v[row].1),
+        ColumnBuffer::Decimal(v) => CompactValue::Decimal(v[row].0, v[row].1, v[row].2),
+        ColumnBuffer::Guid(v) => CompactValue::Guid(v[row]),
+        ColumnBuffer::Boxed(v) => v[row].clone(),
+    }
+}
+
+struct Column {
+    buffer: ColumnBuffer,
+    nulls: NullBitmap,
+}
+
+impl Column {
+    fn new() -> Self {
+        Self { buffer: ColumnBuffer::Unset, nulls: NullBitmap::default() }
+    }
+
+    #[inline]
+    fn get(&self, row: usize) -> CompactValue {
+        boxed_get(&self.buffer, &self.nulls, row)
+    }
+
+    /// Convert whatever's in `self.buffer` (typed or still `Unset`) into a
+    /// `Boxed` column holding one more entry, `val`, appended at the end.
+    fn fall_back_to_boxed(&mut self, val: CompactValue) {
+        let rows = self.nulls.len;
+        let mut boxed: Vec<CompactValue> = (0..rows).map(|r| boxed_get(&self.buffer, &self.nulls, r)).collect();
+        boxed.push(val);
+        self.buffer = ColumnBuffer::Boxed(boxed);
+    }
+
+    /// Dispatch a already-typed `CompactValue` to the matching `push_*`, for
+    /// callers (like `MultiSetWriter`) that buffer a whole row before they
+    /// know whether it's headed into this columnar storage or a `SpillStore`.
+    fn push_value(&mut self, val: CompactValue) {
+        match val {
+            CompactValue::Null => self.push_null(),
+            CompactValue::Bool(v) => self.push_bool(v),
+            CompactValue::I64(v) => self.push_i64(v),
+            CompactValue::F64(v) => self.push_f64(v),
+            CompactValue::Str(v) => self.push_str(&v),
+            CompactValue::Bytes(v) => self.push_bytes(&v),
+            CompactValue::Date(v) => self.push_date(v),
+            CompactValue::Time(v) => self.push_time(v),
+            CompactValue::DateTime(v) => self.push_datetime(v),
+            CompactValue::DateTimeOffset(micros, offset) => self.push_datetimeoffset(micros, offset),
+            CompactValue::Decimal(value, precision, scale) => self.push_decimal(value, precision, scale),
+            CompactValue::Guid(v) => self.push_guid(v),
+        }
+    }
+
+    fn push_null(&mut self) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => {}
+            ColumnBuffer::Bool(v) => v.push(false),
+            ColumnBuffer::I64(v) => v.push(0),
+            ColumnBuffer::F64(v) => v.push(0.0),
+            ColumnBuffer::Str { offsets, data } | ColumnBuffer::Bytes { offsets, data } => offsets.push(data.len() as u32),
+            ColumnBuffer::Date(v) => v.push(0),
+            ColumnBuffer::Time(v) => v.push(0),
+            ColumnBuffer::DateTime(v) => v.push(0),
+            ColumnBuffer::DateTimeOffset(v) => v.push((0, 0)),
+            ColumnBuffer::Decimal(v) => v.push((0, 0, 0)),
+            ColumnBuffer::Guid(v) => v.push([0; 16]),
+            ColumnBuffer::Boxed(v) => v.push(CompactValue::Null),
+            ColumnBuffer::I32(_) | ColumnBuffer::I16(_) | ColumnBuffer::I8(_) => {
+                unreachable!("narrow int columns only exist after shrink_ints, once writing is done")
+            }
+        }
+        self.nulls.push(true);
+    }
+
+    fn push_bool(&mut self, val: bool) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => self.buffer = ColumnBuffer::Bool(vec![false; self.nulls.len].tap_push(val)),
+            ColumnBuffer::Bool(v) => v.push(val),
+            _ => self.fall_back_to_boxed(CompactValue::Bool(val)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_i64(&mut self, val: i64) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => self.buffer = ColumnBuffer::I64(vec![0i64; self.nulls.len].tap_push(val)),
+            ColumnBuffer::I64(v) => v.push(val),
+            _ => self.fall_back_to_boxed(CompactValue::I64(val)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_f64(&mut self, val: f64) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => self.buffer = ColumnBuffer::F64(vec![0.0f64; self.nulls.len].tap_push(val)),
+            ColumnBuffer::F64(v) => v.push(val),
+            _ => self.fall_back_to_boxed(CompactValue::F64(val)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_str(&mut self, val: &str) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => {
+                let mut offsets = vec![0u32; self.nulls.len];
+                let mut data = Vec::new();
+                data.extend_from_slice(val.as_bytes());
+                offsets.push(data.len() as u32);
+                self.buffer = ColumnBuffer::Str { offsets, data };
+            }
+            ColumnBuffer::Str { offsets, data } => {
+                data.extend_from_slice(val.as_bytes());
+                offsets.push(data.len() as u32);
+            }
+            _ => self.fall_back_to_boxed(CompactValue::Str(val.to_owned())),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_bytes(&mut self, val: &[u8]) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => {
+                let mut offsets = vec![0u32; self.nulls.len];
+                let mut data = Vec::new();
+                data.extend_from_slice(val);
+                offsets.push(data.len() as u32);
+                self.buffer = ColumnBuffer::Bytes { offsets, data };
+            }
+            ColumnBuffer::Bytes { offsets, data } => {
+                data.extend_from_slice(val);
+                offsets.push(data.len() as u32);
+            }
+            _ => self.fall_back_to_boxed(CompactValue::Bytes(val.to_owned())),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_date(&mut self, val: i32) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => self.buffer = ColumnBuffer::Date(vec![0i32; self.nulls.len].tap_push(val)),
+            ColumnBuffer::Date(v) => v.push(val),
+            _ => self.fall_back_to_boxed(CompactValue::Date(val)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_time(&mut self, val: i64) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => self.buffer = ColumnBuffer::Time(vec![0i64; self.nulls.len].tap_push(val)),
+            ColumnBuffer::Time(v) => v.push(val),
+            _ => self.fall_back_to_boxed(CompactValue::Time(val)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_datetime(&mut self, val: i64) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => self.buffer = ColumnBuffer::DateTime(vec![0i64; self.nulls.len].tap_push(val)),
+            ColumnBuffer::DateTime(v) => v.push(val),
+            _ => self.fall_back_to_boxed(CompactValue::DateTime(val)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_datetimeoffset(&mut self, micros: i64, offset_minutes: i16) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => {
+                self.buffer = ColumnBuffer::DateTimeOffset(vec![(0, 0); self.nulls.len].tap_push((micros, offset_minutes)))
+            }
+            ColumnBuffer::DateTimeOffset(v) => v.push((micros, offset_minutes)),
+            _ => self.fall_back_to_boxed(CompactValue::DateTimeOffset(micros, offset_minutes)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_decimal(&mut self, value: i128, precision: u8, scale: u8) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => {
+                self.buffer = ColumnBuffer::Decimal(vec![(0, 0, 0); self.nulls.len].tap_push((value, precision, scale)))
+            }
+            ColumnBuffer::Decimal(v) => v.push((value, precision, scale)),
+            _ => self.fall_back_to_boxed(CompactValue::Decimal(value, precision, scale)),
+        }
+        self.nulls.push(false);
+    }
+
+    fn push_guid(&mut self, val: [u8; 16]) {
+        match &mut self.buffer {
+            ColumnBuffer::Unset => self.buffer = ColumnBuffer::Guid(vec![[0u8; 16]; self.nulls.len].tap_push(val)),
+            ColumnBuffer::Guid(v) => v.push(val),
+            _ => self.fall_back_to_boxed(CompactValue::Guid(val)),
+        }
+        self.nulls.push(false);
+    }
+
+    /// Downcast a finished `I64` column to the narrowest integer width that
+    /// still fits its observed min/max, the way rmp picks the most compact
+    /// marker for an `EfficientInt`. Only worth doing once writing is done -
+    /// the running min/max isn't known until then.
+    fn shrink_ints(&mut self) {
+        if let ColumnBuffer::I64(v) = &self.buffer {
+            if v.is_empty() {
+                return;
+            }
+            let (min, max) = v.iter().fold((i64::MAX, i64::MIN), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+            self.buffer = if min >= i8::MIN as i64 && max <= i8::MAX as i64 {
+                ColumnBuffer::I8(v.iter().map(|&x| x as i8).collect())
+            } else if min >= i16::MIN as i64 && max <= i16::MAX as i64 {
+                ColumnBuffer::I16(v.iter().map(|&x| x as i16).collect())
+            } else if min >= i32::MIN as i64 && max <= i32::MAX as i64 {
+                ColumnBuffer::I32(v.iter().map(|&x| x as i32).collect())
+            } else {
+                return;
+            };
+        }
+    }
+}
+
+/// Tiny builder-style helper so the `Unset`-materialization arms above can
+/// stay one expression (`vec![default; n].tap_push(val)`) instead of a
+/// three-statement block.
+trait TapPush<T> {
+    fn tap_push(self, val: T) -> Vec<T>;
+}
+
+impl<T> TapPush<T> for Vec<T> {
+    fn tap_push(mut self, val: T) -> Vec<T> {
+        self.push(val);
+        self
+    }
+}
+
 pub struct PyRowWriter {
     pub col_count: usize,
-    pub values: Vec<CompactValue>,
-    current_row: Vec<CompactValue>,
+    columns: Vec<Column>,
+    row_count: usize,
 }
 
 impl PyRowWriter {
     pub fn new(col_count: usize) -> Self {
-        Self {
-            col_count,
-            values: Vec::with_capacity(col_count * 64),
-            current_row: Vec::with_capacity(col_count),
-        }
+        Self { col_count, columns: (0..col_count).map(|_| Column::new()).collect(), row_count: 0 }
     }
 
     pub fn row_count(&self) -> usize {
-        if self.col_count == 0 { 0 } else { self.values.len() / self.col_count }
+        self.row_count
     }
 
     #[inline]
-    pub fn get(&self, row: usize, col: usize) -> &CompactValue {
-        &self.values[row * self.col_count + col]
+    pub fn get(&self, row: usize, col: usize) -> CompactValue {
+        self.columns[col].get(row)
+    }
+
+    fn push_value(&mut self, col: usize, val: CompactValue) {
+        self.columns[col].push_value(val);
     }
 
     fn finish_row(&mut self) {
-        self.values.append(&mut self.current_row);
+        self.row_count += 1;
     }
 
-    #[inline]
-    fn push(&mut self, val: CompactValue) {
-        self.current_row.push(val);
+    fn shrink_ints(&mut self) {
+        for column in &mut self.columns {
+            column.shrink_ints();
+        }
     }
 }
 
@@ -55,56 +363,250 @@ pub struct ColumnInfo {
     pub name: String,
 }
 
+/// A finished result set, either held in memory in its columnar layout or,
+/// past `MultiSetWriter`'s spill threshold, on disk as length-delimited rows.
+/// `get` takes `&mut self` since replaying a spilled row needs to seek the
+/// backing file.
+pub enum RowSet {
+    Memory(PyRowWriter),
+    Spilled(SpillStore),
+}
+
+impl RowSet {
+    pub fn row_count(&self) -> usize {
+        match self {
+            RowSet::Memory(w) => w.row_count(),
+            RowSet::Spilled(s) => s.row_count(),
+        }
+    }
+
+    pub fn col_count(&self) -> usize {
+        match self {
+            RowSet::Memory(w) => w.col_count,
+            RowSet::Spilled(s) => s.col_count(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&mut self, row: usize, col: usize) -> CompactValue {
+        match self {
+            RowSet::Memory(w) => w.get(row, col),
+            RowSet::Spilled(s) => s.get(row, col),
+        }
+    }
+
+    fn shrink_ints(&mut self) {
+        if let RowSet::Memory(w) = self {
+            w.shrink_ints();
+        }
+    }
+}
+
+/// Rough on-the-wire size of one cell, used only to decide when a result set
+/// has crossed `MultiSetWriter`'s spill threshold - doesn't need to be exact,
+/// just proportional to what's actually being held in memory.
+fn cell_size(val: &CompactValue) -> usize {
+    match val {
+        CompactValue::Null | CompactValue::Bool(_) => 1,
+        CompactValue::Date(_) => 4,
+        CompactValue::I64(_) | CompactValue::F64(_) | CompactValue::Time(_) | CompactValue::DateTime(_) => 8,
+        CompactValue::DateTimeOffset(_, _) => 10,
+        CompactValue::Decimal(_, _, _) | CompactValue::Guid(_) => 16,
+        CompactValue::Str(v) => v.len(),
+        CompactValue::Bytes(v) => v.len(),
+    }
+}
+
+/// Buffers every `RowWriter` callback into one or more result sets, spilling
+/// a result set to a temp file once it crosses an optional byte threshold so
+/// a query with a huge result doesn't have to fit entirely in memory.
+///
+/// Cells for the row in progress land in `current_row` rather than going
+/// straight into `current`'s columnar storage, since whether that row ends
+/// up in memory or on disk isn't decided until `on_row_done` checks
+/// `current_bytes` against `spill_threshold`.
 pub struct MultiSetWriter {
-    pub completed: Vec<(Vec<ColumnInfo>, PyRowWriter)>,
+    pub completed: Vec<(Vec<ColumnInfo>, RowSet)>,
     current_cols: Option<Vec<ColumnInfo>>,
-    current: Option<PyRowWriter>,
+    current: Option<RowSet>,
+    current_row: Vec<CompactValue>,
+    current_bytes: usize,
+    spill_threshold: Option<usize>,
 }
 
 impl MultiSetWriter {
     pub fn new() -> Self {
-        Self { completed: Vec::new(), current_cols: None, current: None }
+        Self {
+            completed: Vec::new(),
+            current_cols: None,
+            current: None,
+            current_row: Vec::new(),
+            current_bytes: 0,
+            spill_threshold: None,
+        }
     }
 
-    pub fn finalize(mut self) -> Vec<(Vec<ColumnInfo>, PyRowWriter)> {
-        if let (Some(cols), Some(writer)) = (self.current_cols.take(), self.current.take()) {
-            self.completed.push((cols, writer));
+    /// Same as `new`, but once the in-progress result set's buffered rows
+    /// cross `threshold_bytes`, move everything buffered so far (and every
+    /// row after) to a temp file instead of growing the in-memory buffer.
+    pub fn with_spill_threshold(threshold_bytes: usize) -> Self {
+        Self { spill_threshold: Some(threshold_bytes), ..Self::new() }
+    }
+
+    fn flush_current(&mut self) {
+        if let (Some(cols), Some(mut set)) = (self.current_cols.take(), self.current.take()) {
+            set.shrink_ints();
+            self.completed.push((cols, set));
         }
+    }
+
+    pub fn finalize(mut self) -> Vec<(Vec<ColumnInfo>, RowSet)> {
+        self.flush_current();
         self.completed
     }
+
+    #[inline]
+    fn set_cell(&mut self, col: usize, val: CompactValue) {
+        if self.current.is_none() {
+            return;
+        }
+        self.current_bytes += cell_size(&val);
+        self.current_row[col] = val;
+    }
+
+    /// Move the in-progress `Memory` result set to a `SpillStore` once it's
+    /// over threshold, replaying its already-buffered rows onto disk first.
+    /// A no-op once already `Spilled`, or under threshold, or empty (an
+    /// all-NULL first row could already be over threshold by cell count
+    /// alone, but there's nothing to gain from spilling zero rows).
+    fn maybe_spill(&mut self, col_count: usize) {
+        let Some(threshold) = self.spill_threshold else { return };
+        if self.current_bytes < threshold {
+            return;
+        }
+        let Some(RowSet::Memory(writer)) = self.current.as_ref() else { return };
+        if writer.row_count() == 0 {
+            return;
+        }
+        if let Some(RowSet::Memory(writer)) = self.current.take() {
+            let mut store = SpillStore::create(col_count).expect("create spill file");
+            for r in 0..writer.row_count() {
+                let row: Vec<CompactValue> = (0..col_count).map(|c| writer.get(r, c)).collect();
+                store.append_row(&row).expect("write spilled row");
+            }
+            self.current = Some(RowSet::Spilled(store));
+        }
+    }
 }
 
 impl RowWriter for MultiSetWriter {
     fn on_metadata(&mut self, columns: &[tabby::Column]) {
-        if let (Some(cols), Some(writer)) = (self.current_cols.take(), self.current.take()) {
-            self.completed.push((cols, writer));
-        }
+        self.flush_current();
         let col_infos: Vec<ColumnInfo> = columns.iter().map(|c| ColumnInfo { name: c.name().to_string() }).collect();
         let col_count = columns.len();
         self.current_cols = Some(col_infos);
-        self.current = Some(PyRowWriter::new(col_count));
+        self.current = Some(RowSet::Memory(PyRowWriter::new(col_count)));
+        self.current_row = vec![CompactValue::Null; col_count];
+        self.current_bytes = 0;
     }
 
     fn on_row_done(&mut self) {
-        if let Some(ref mut w) = self.current { w.finish_row(); }
+        let col_count = self.current_row.len();
+        if self.current.is_none() || col_count == 0 {
+            return;
+        }
+        let row = std::mem::replace(&mut self.current_row, vec![CompactValue::Null; col_count]);
+        match self.current.as_mut().unwrap() {
+            RowSet::Memory(w) => {
+                for (c, val) in row.into_iter().enumerate() {
+                    w.push_value(c, val);
+                }
+                w.finish_row();
+            }
+            RowSet::Spilled(s) => s.append_row(&row).expect("write spilled row"),
+        }
+        self.maybe_spill(col_count);
     }
 
     fn on_info(&mut self, _number: u32, _message: &str) {}
 
-    #[inline] fn write_null(&mut self, _col: usize) { if let Some(ref mut w) = self.current { w.push(CompactValue::Null); } }
-    #[inline] fn write_bool(&mut self, _col: usize, val: bool) { if let Some(ref mut w) = self.current { w.push(CompactValue::Bool(val)); } }
-    #[inline] fn write_u8(&mut self, _col: usize, val: u8) { if let Some(ref mut w) = self.current { w.push(CompactValue::I64(val as i64)); } }
-    #[inline] fn write_i16(&mut self, _col: usize, val: i16) { if let Some(ref mut w) = self.current { w.push(CompactValue::I64(val as i64)); } }
-    #[inline] fn write_i32(&mut self, _col: usize, val: i32) { if let Some(ref mut w) = self.current { w.push(CompactValue::I64(val as i64)); } }
-    #[inline] fn write_i64(&mut self, _col: usize, val: i64) { if let Some(ref mut w) = self.current { w.push(CompactValue::I64(val)); } }
-    #[inline] fn write_f32(&mut self, _col: usize, val: f32) { if let Some(ref mut w) = self.current { w.push(CompactValue::F64(val as f64)); } }
-    #[inline] fn write_f64(&mut self, _col: usize, val: f64) { if let Some(ref mut w) = self.current { w.push(CompactValue::F64(val)); } }
-    #[inline] fn write_str(&mut self, _col: usize, val: &str) { if let Some(ref mut w) = self.current { w.push(CompactValue::Str(val.to_owned())); } }
-    #[inline] fn write_bytes(&mut self, _col: usize, val: &[u8]) { if let Some(ref mut w) = self.current { w.push(CompactValue::Bytes(val.to_owned())); } }
-    #[inline] fn write_date(&mut self, _col: usize, days: i32) { if let Some(ref mut w) = self.current { w.push(CompactValue::Date(days)); } }
-    #[inline] fn write_time(&mut self, _col: usize, nanos: i64) { if let Some(ref mut w) = self.current { w.push(CompactValue::Time(nanos)); } }
-    #[inline] fn write_datetime(&mut self, _col: usize, micros: i64) { if let Some(ref mut w) = self.current { w.push(CompactValue::DateTime(micros)); } }
-    #[inline] fn write_datetimeoffset(&mut self, _col: usize, micros: i64, offset_minutes: i16) { if let Some(ref mut w) = self.current { w.push(CompactValue::DateTimeOffset(micros, offset_minutes)); } }
-    #[inline] fn write_decimal(&mut self, _col: usize, value: i128, precision: u8, scale: u8) { if let Some(ref mut w) = self.current { w.push(CompactValue::Decimal(value, precision, scale)); } }
-    #[inline] fn write_guid(&mut self, _col: usize, bytes: &[u8; 16]) { if let Some(ref mut w) = self.current { w.push(CompactValue::Guid(*bytes)); } }
+    #[inline] fn write_null(&mut self, col: usize) { self.set_cell(col, CompactValue::Null); }
+    #[inline] fn write_bool(&mut self, col: usize, val: bool) { self.set_cell(col, CompactValue::Bool(val)); }
+    #[inline] fn write_u8(&mut self, col: usize, val: u8) { self.set_cell(col, CompactValue::I64(val as i64)); }
+    #[inline] fn write_i16(&mut self, col: usize, val: i16) { self.set_cell(col, CompactValue::I64(val as i64)); }
+    #[inline] fn write_i32(&mut self, col: usize, val: i32) { self.set_cell(col, CompactValue::I64(val as i64)); }
+    #[inline] fn write_i64(&mut self, col: usize, val: i64) { self.set_cell(col, CompactValue::I64(val)); }
+    #[inline] fn write_f32(&mut self, col: usize, val: f32) { self.set_cell(col, CompactValue::F64(val as f64)); }
+    #[inline] fn write_f64(&mut self, col: usize, val: f64) { self.set_cell(col, CompactValue::F64(val)); }
+    #[inline] fn write_str(&mut self, col: usize, val: &str) { self.set_cell(col, CompactValue::Str(val.to_owned())); }
+    #[inline] fn write_bytes(&mut self, col: usize, val: &[u8]) { self.set_cell(col, CompactValue::Bytes(val.to_owned())); }
+    #[inline] fn write_date(&mut self, col: usize, days: i32) { self.set_cell(col, CompactValue::Date(days)); }
+    #[inline] fn write_time(&mut self, col: usize, nanos: i64) { self.set_cell(col, CompactValue::Time(nanos)); }
+    #[inline] fn write_datetime(&mut self, col: usize, micros: i64) { self.set_cell(col, CompactValue::DateTime(micros)); }
+    #[inline] fn write_datetimeoffset(&mut self, col: usize, micros: i64, offset_minutes: i16) { self.set_cell(col, CompactValue::DateTimeOffset(micros, offset_minutes)); }
+    #[inline] fn write_decimal(&mut self, col: usize, value: i128, precision: u8, scale: u8) { self.set_cell(col, CompactValue::Decimal(value, precision, scale)); }
+    #[inline] fn write_guid(&mut self, col: usize, bytes: &[u8; 16]) { self.set_cell(col, CompactValue::Guid(*bytes)); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn as_i64(val: CompactValue) -> i64 {
+        match val {
+            CompactValue::I64(v) => v,
+            other => panic!("expected I64, got a different CompactValue variant ({:?})", std::mem::discriminant(&other)),
+        }
+    }
+
+    #[test]
+    fn typed_column_round_trips_matching_values() {
+        let mut w = PyRowWriter::new(1);
+        w.push_value(0, CompactValue::I64(1));
+        w.push_value(0, CompactValue::I64(2));
+        w.push_value(0, CompactValue::I64(3));
+        assert_eq!(as_i64(w.get(0, 0)), 1);
+        assert_eq!(as_i64(w.get(1, 0)), 2);
+        assert_eq!(as_i64(w.get(2, 0)), 3);
+    }
+
+    #[test]
+    fn null_then_typed_value_preserves_null_at_its_row() {
+        let mut w = PyRowWriter::new(1);
+        w.push_value(0, CompactValue::Null);
+        w.push_value(0, CompactValue::I64(42));
+        assert!(matches!(w.get(0, 0), CompactValue::Null));
+        assert_eq!(as_i64(w.get(1, 0)), 42);
+    }
+
+    #[test]
+    fn mismatched_types_fall_back_to_boxed_without_losing_earlier_rows() {
+        let mut w = PyRowWriter::new(1);
+        w.push_value(0, CompactValue::I64(7));
+        w.push_value(0, CompactValue::Str("hi".to_string()));
+        assert_eq!(as_i64(w.get(0, 0)), 7);
+        match w.get(1, 0) {
+            CompactValue::Str(s) => assert_eq!(s, "hi"),
+            other => panic!("expected Str, got a different CompactValue variant ({:?})", std::mem::discriminant(&other)),
+        }
+    }
+
+    #[test]
+    fn shrink_ints_narrows_column_without_changing_observed_values() {
+        let mut w = PyRowWriter::new(1);
+        w.push_value(0, CompactValue::I64(-5));
+        w.push_value(0, CompactValue::I64(100));
+        w.shrink_ints();
+        assert!(matches!(w.columns[0].buffer, ColumnBuffer::I8(_)));
+        assert_eq!(as_i64(w.get(0, 0)), -5);
+        assert_eq!(as_i64(w.get(1, 0)), 100);
+    }
+
+    #[test]
+    fn shrink_ints_leaves_wide_values_as_i64() {
+        let mut w = PyRowWriter::new(1);
+        w.push_value(0, CompactValue::I64(i64::from(i32::MAX) + 1));
+        w.shrink_ints();
+        assert!(matches!(w.columns[0].buffer, ColumnBuffer::I64(_)));
+    }
 }
@@ -0,0 +1,402 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::row_writer::CompactValue;
+
+const SPILL_BUF_SIZE: usize = 8192;
+
+/// Protobuf-style buffered writer: an internal fixed-size buffer that's
+/// flushed to the file in one `write_all` once full, rather than growing a
+/// `Vec<u8>` and copying it into a second buffer before the real write.
+pub struct CodedOutputStream {
+    file: File,
+    buf: [u8; SPILL_BUF_SIZE],
+    pos: usize,
+    /// Bytes already handed to `file` via `flush_buf`; added to `pos` this
+    /// gives the absolute offset the next write will land at.
+    flushed: u64,
+}
+
+impl CodedOutputStream {
+    pub fn new(file: File) -> Self {
+        Self { file, buf: [0; SPILL_BUF_SIZE], pos: 0, flushed: 0 }
+    }
+
+    /// Absolute byte offset the next write will start at.
+    pub fn position(&self) -> u64 {
+        self.flushed + self.pos as u64
+    }
+
+    fn flush_buf(&mut self) -> io::Result<()> {
+        if self.pos > 0 {
+            self.file.write_all(&self.buf[..self.pos])?;
+            self.flushed += self.pos as u64;
+            self.pos = 0;
+        }
+        Ok(())
+    }
+
+    pub fn write_raw_byte(&mut self, byte: u8) -> io::Result<()> {
+        if self.pos == SPILL_BUF_SIZE {
+            self.flush_buf()?;
+        }
+        self.buf[self.pos] = byte;
+        self.pos += 1;
+        Ok(())
+    }
+
+    pub fn write_raw_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        for &b in bytes {
+            self.write_raw_byte(b)?;
+        }
+        Ok(())
+    }
+
+    /// Base-128 little-endian varint: 7 payload bits per byte, high bit set
+    /// on every byte but the last.
+    pub fn write_raw_varint(&mut self, mut value: u64) -> io::Result<()> {
+        loop {
+            if value < 0x80 {
+                return self.write_raw_byte(value as u8);
+            }
+            self.write_raw_byte((value as u8 & 0x7f) | 0x80)?;
+            value >>= 7;
+        }
+    }
+
+    /// Zigzag-encode a signed value (`(n << 1) ^ (n >> 63)`) so small
+    /// negative numbers stay small on the wire instead of sign-extending to
+    /// ten bytes of `1`s.
+    pub fn write_raw_varint_signed(&mut self, value: i64) -> io::Result<()> {
+        let zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        self.write_raw_varint(zigzag)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.flush_buf()?;
+        self.file.flush()
+    }
+
+    pub fn file_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+}
+
+/// Cursor over an in-memory record's bytes, the read-side counterpart of
+/// `CodedOutputStream`'s varint writer.
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let b = self.data[self.pos];
+        self.pos += 1;
+        b
+    }
+
+    fn read_bytes(&mut self, n: usize) -> &'a [u8] {
+        let s = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        s
+    }
+
+    fn read_varint(&mut self) -> u64 {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = self.read_byte();
+            result |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        result
+    }
+
+    fn read_varint_signed(&mut self) -> i64 {
+        let zigzag = self.read_varint();
+        ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64)
+    }
+}
+
+fn read_varint_from_file(file: &mut File) -> io::Result<u64> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        file.read_exact(&mut byte)?;
+        result |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    Ok(result)
+}
+
+/// One-byte type tags for a spilled cell, same set as `CompactValue`'s
+/// variants. Tags, not `CompactValue`'s in-memory layout, are what's stable
+/// on disk.
+fn encode_cell(buf: &mut Vec<u8>, val: &CompactValue) {
+    match val {
+        CompactValue::Null => buf.push(0),
+        CompactValue::Bool(v) => {
+            buf.push(1);
+            buf.push(*v as u8);
+        }
+        CompactValue::I64(v) => {
+            buf.push(2);
+            push_varint_signed(buf, *v);
+        }
+        CompactValue::F64(v) => {
+            buf.push(3);
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        CompactValue::Str(s) => {
+            buf.push(4);
+            push_varint(buf, s.len() as u64);
+            buf.extend_from_slice(s.as_bytes());
+        }
+        CompactValue::Bytes(b) => {
+            buf.push(5);
+            push_varint(buf, b.len() as u64);
+            buf.extend_from_slice(b);
+        }
+        CompactValue::Date(d) => {
+            buf.push(6);
+            push_varint_signed(buf, *d as i64);
+        }
+        CompactValue::Time(t) => {
+            buf.push(7);
+            push_varint_signed(buf, *t);
+        }
+        CompactValue::DateTime(t) => {
+            buf.push(8);
+            push_varint_signed(buf, *t);
+        }
+        CompactValue::DateTimeOffset(micros, offset) => {
+            buf.push(9);
+            push_varint_signed(buf, *micros);
+            push_varint_signed(buf, *offset as i64);
+        }
+        CompactValue::Decimal(value, precision, scale) => {
+            buf.push(10);
+            buf.extend_from_slice(&value.to_be_bytes());
+            buf.push(*precision);
+            buf.push(*scale);
+        }
+        CompactValue::Guid(g) => {
+            buf.push(11);
+            buf.extend_from_slice(g);
+        }
+    }
+}
+
+fn decode_cell(r: &mut ByteReader) -> CompactValue {
+    match r.read_byte() {
+        0 => CompactValue::Null,
+        1 => CompactValue::Bool(r.read_byte() != 0),
+        2 => CompactValue::I64(r.read_varint_signed()),
+        3 => CompactValue::F64(f64::from_be_bytes(r.read_bytes(8).try_into().unwrap())),
+        4 => {
+            let len = r.read_varint() as usize;
+            CompactValue::Str(String::from_utf8_lossy(r.read_bytes(len)).into_owned())
+        }
+        5 => {
+            let len = r.read_varint() as usize;
+            CompactValue::Bytes(r.read_bytes(len).to_vec())
+        }
+        6 => CompactValue::Date(r.read_varint_signed() as i32),
+        7 => CompactValue::Time(r.read_varint_signed()),
+        8 => CompactValue::DateTime(r.read_varint_signed()),
+        9 => CompactValue::DateTimeOffset(r.read_varint_signed(), r.read_varint_signed() as i16),
+        10 => CompactValue::Decimal(
+            i128::from_be_bytes(r.read_bytes(16).try_into().unwrap()),
+            r.read_byte(),
+            r.read_byte(),
+        ),
+        11 => CompactValue::Guid(r.read_bytes(16).try_into().unwrap()),
+        tag => unreachable!("unknown spilled cell tag {tag}"),
+    }
+}
+
+/// Same bit-twiddling as `CodedOutputStream::write_raw_varint`, duplicated
+/// here because a row has to be length-prefixed before we know its encoded
+/// size - it's built into a plain `Vec<u8>` first and only handed to the
+/// buffered file stream (prefixed with that length) once it's complete.
+fn push_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        if value < 0x80 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+}
+
+fn push_varint_signed(buf: &mut Vec<u8>, value: i64) {
+    let zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    push_varint(buf, zigzag);
+}
+
+/// Rows spilled to a temp file as length-delimited records (a varint byte
+/// count followed by that many cell-encoded bytes), once a result set grows
+/// past `MultiSetWriter`'s spill threshold. `offsets` gives each row's
+/// starting byte position so `get` can seek straight to it instead of
+/// replaying the file from the start.
+pub struct SpillStore {
+    stream: CodedOutputStream,
+    offsets: Vec<u64>,
+    col_count: usize,
+}
+
+impl SpillStore {
+    pub fn create(col_count: usize) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("hiss-spill-{}.bin", uuid::Uuid::new_v4()));
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path)?;
+        // Unlink right away so the file is reclaimed as soon as our handle
+        // closes; nothing else ever needs the path. Windows can't delete an
+        // open file, so there the temp file outlives us until OS cleanup.
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&path);
+        Ok(Self { stream: CodedOutputStream::new(file), offsets: Vec::new(), col_count })
+    }
+
+    pub fn append_row(&mut self, cells: &[CompactValue]) -> io::Result<()> {
+        let mut row_bytes = Vec::new();
+        for cell in cells {
+            encode_cell(&mut row_bytes, cell);
+        }
+        self.offsets.push(self.stream.position());
+        self.stream.write_raw_varint(row_bytes.len() as u64)?;
+        self.stream.write_raw_bytes(&row_bytes)
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn col_count(&self) -> usize {
+        self.col_count
+    }
+
+    /// Replay one row from disk: seek to its offset, read the varint length
+    /// prefix, decode the row's cells and return the one at `col`. The file
+    /// is left positioned at its end so later appends land after the data.
+    pub fn get(&mut self, row: usize, col: usize) -> CompactValue {
+        self.stream.flush().expect("flush spill buffer before read");
+        let file = self.stream.file_mut();
+        file.seek(SeekFrom::Start(self.offsets[row])).expect("seek to spilled row");
+        let len = read_varint_from_file(file).expect("read spilled row length") as usize;
+        let mut buf = vec![0u8; len];
+        file.read_exact(&mut buf).expect("read spilled row bytes");
+        file.seek(SeekFrom::End(0)).expect("seek back to end of spill file");
+        let mut r = ByteReader::new(&buf);
+        let mut result = CompactValue::Null;
+        for c in 0..self.col_count {
+            let cell = decode_cell(&mut r);
+            if c == col {
+                result = cell;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_round_trips_small_and_multibyte_values() {
+        for &v in &[0u64, 1, 127, 128, 300, u64::MAX] {
+            let mut buf = Vec::new();
+            push_varint(&mut buf, v);
+            let mut r = ByteReader::new(&buf);
+            assert_eq!(r.read_varint(), v);
+        }
+    }
+
+    #[test]
+    fn varint_uses_continuation_bit_past_seven_bits() {
+        let mut buf = Vec::new();
+        push_varint(&mut buf, 127);
+        assert_eq!(buf, vec![0x7f]);
+
+        buf.clear();
+        push_varint(&mut buf, 128);
+        assert_eq!(buf, vec![0x80, 0x01]);
+    }
+
+    #[test]
+    fn signed_varint_zigzags_small_negatives_to_small_encodings() {
+        let mut buf = Vec::new();
+        push_varint_signed(&mut buf, -1);
+        assert_eq!(buf, vec![0x01]); // zigzag(-1) == 1, fits in one byte
+
+        buf.clear();
+        push_varint_signed(&mut buf, 1);
+        assert_eq!(buf, vec![0x02]); // zigzag(1) == 2
+    }
+
+    #[test]
+    fn signed_varint_round_trips_across_the_i64_range() {
+        for &v in &[0i64, -1, 1, i64::MIN, i64::MAX, -12345, 12345] {
+            let mut buf = Vec::new();
+            push_varint_signed(&mut buf, v);
+            let mut r = ByteReader::new(&buf);
+            assert_eq!(r.read_varint_signed(), v);
+        }
+    }
+
+    #[test]
+    fn encode_decode_cell_round_trips_every_variant() {
+        let cells = vec![
+            CompactValue::Null,
+            CompactValue::Bool(true),
+            CompactValue::I64(-42),
+            CompactValue::F64(3.5),
+            CompactValue::Str("hello".to_string()),
+            CompactValue::Bytes(vec![1, 2, 3]),
+            CompactValue::Date(19000),
+            CompactValue::Time(123456789),
+            CompactValue::DateTime(987654321),
+            CompactValue::DateTimeOffset(987654321, -300),
+            CompactValue::Decimal(123456789, 18, 4),
+            CompactValue::Guid([7u8; 16]),
+        ];
+        for cell in cells {
+            let mut buf = Vec::new();
+            encode_cell(&mut buf, &cell);
+            let mut r = ByteReader::new(&buf);
+            let decoded = decode_cell(&mut r);
+            assert_eq!(format!("{:?}", std::mem::discriminant(&decoded)), format!("{:?}", std::mem::discriminant(&cell)));
+            let mut reencoded = Vec::new();
+            encode_cell(&mut reencoded, &decoded);
+            assert_eq!(reencoded, buf);
+        }
+    }
+
+    #[test]
+    fn spill_store_round_trips_rows_written_to_disk() {
+        let mut store = SpillStore::create(2).expect("create spill file");
+        store.append_row(&[CompactValue::I64(1), CompactValue::Str("a".to_string())]).unwrap();
+        store.append_row(&[CompactValue::I64(2), CompactValue::Str("bb".to_string())]).unwrap();
+
+        assert_eq!(store.row_count(), 2);
+        assert!(matches!(store.get(0, 0), CompactValue::I64(1)));
+        assert!(matches!(store.get(1, 0), CompactValue::I64(2)));
+        match store.get(1, 1) {
+            CompactValue::Str(s) => assert_eq!(s, "bb"),
+            other => panic!("expected Str, got a different CompactValue variant ({:?})", std::mem::discriminant(&other)),
+        }
+    }
+}
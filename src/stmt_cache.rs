@@ -0,0 +1,157 @@
+use std::collections::{HashMap, VecDeque};
+
+use tabby::ColumnData;
+
+/// The handle-relevant part of a bound parameter: enough to tell two calls
+/// apart when SQL Server would need a different `sp_prepare` parameter
+/// declaration for them, without pinning the cache key to the parameter's
+/// actual value (so the same query in a loop keeps hitting the same handle).
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum ParamSig {
+    Null,
+    Bit,
+    I64,
+    F64,
+    Numeric(u8, u8),
+    DateTime2,
+    Date,
+    Time,
+    Guid,
+    Binary,
+    String,
+}
+
+impl ParamSig {
+    fn of(param: &ColumnData<'static>) -> Self {
+        match param {
+            ColumnData::None => ParamSig::Null,
+            ColumnData::Bit(_) => ParamSig::Bit,
+            ColumnData::I64(_) => ParamSig::I64,
+            ColumnData::F64(_) => ParamSig::F64,
+            ColumnData::Numeric(_, precision, scale) => ParamSig::Numeric(*precision, *scale),
+            ColumnData::DateTime2(_) => ParamSig::DateTime2,
+            ColumnData::Date(_) => ParamSig::Date,
+            ColumnData::Time(_) => ParamSig::Time,
+            ColumnData::Guid(_) => ParamSig::Guid,
+            ColumnData::Binary(_) => ParamSig::Binary,
+            ColumnData::String(_) => ParamSig::String,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    sql: String,
+    sig: Vec<ParamSig>,
+}
+
+impl CacheKey {
+    fn new(sql: &str, params: &[ColumnData<'static>]) -> Self {
+        Self { sql: sql.to_string(), sig: params.iter().map(ParamSig::of).collect() }
+    }
+}
+
+/// A per-session cache from `(sql, param types)` to the `sp_prepare` handle
+/// SQL Server gave us for it, evicted least-recently-used first once it's
+/// past `Statement Cache Size`. Handles are only valid on the physical
+/// session that prepared them, so this rides alongside one `TdsClient`
+/// rather than being shared across the pool - see `PooledClient`.
+pub struct StmtCache {
+    capacity: usize,
+    order: VecDeque<CacheKey>,
+    handles: HashMap<CacheKey, i32>,
+}
+
+impl StmtCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, order: VecDeque::new(), handles: HashMap::new() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn get(&mut self, sql: &str, params: &[ColumnData<'static>]) -> Option<i32> {
+        let key = CacheKey::new(sql, params);
+        let handle = *self.handles.get(&key)?;
+        if let Some(pos) = self.order.iter().position(|k| *k == key) {
+            let k = self.order.remove(pos).expect("position just found");
+            self.order.push_back(k);
+        }
+        Some(handle)
+    }
+
+    /// Record a freshly prepared handle, returning the handle evicted to make
+    /// room for it, if any - the caller is responsible for `sp_unprepare`ing it.
+    pub fn insert(&mut self, sql: &str, params: &[ColumnData<'static>], handle: i32) -> Option<i32> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let key = CacheKey::new(sql, params);
+        self.handles.insert(key.clone(), handle);
+        self.order.push_back(key);
+        if self.order.len() > self.capacity {
+            let evicted_key = self.order.pop_front().expect("just checked len > capacity >= 1");
+            return self.handles.remove(&evicted_key);
+        }
+        None
+    }
+}
+
+impl Default for StmtCache {
+    fn default() -> Self {
+        StmtCache::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_cache_never_returns_a_hit() {
+        let mut cache = StmtCache::default();
+        assert!(!cache.is_enabled());
+        assert_eq!(cache.insert("select 1", &[], 1), None);
+        assert_eq!(cache.get("select 1", &[]), None);
+    }
+
+    #[test]
+    fn same_sql_and_param_types_hit_the_cache() {
+        let mut cache = StmtCache::new(2);
+        cache.insert("select @p1", &[ColumnData::I64(1)], 10);
+        assert_eq!(cache.get("select @p1", &[ColumnData::I64(2)]), Some(10));
+    }
+
+    #[test]
+    fn different_param_types_for_the_same_sql_miss() {
+        let mut cache = StmtCache::new(2);
+        cache.insert("select @p1", &[ColumnData::I64(1)], 10);
+        assert_eq!(cache.get("select @p1", &[ColumnData::String(std::borrow::Cow::Borrowed("x"))]), None);
+    }
+
+    #[test]
+    fn eviction_drops_the_least_recently_used_entry() {
+        let mut cache = StmtCache::new(2);
+        assert_eq!(cache.insert("a", &[], 1), None);
+        assert_eq!(cache.insert("b", &[], 2), None);
+        let evicted = cache.insert("c", &[], 3);
+        assert_eq!(evicted, Some(1));
+        assert_eq!(cache.get("a", &[]), None);
+        assert_eq!(cache.get("b", &[]), Some(2));
+        assert_eq!(cache.get("c", &[]), Some(3));
+    }
+
+    #[test]
+    fn get_marks_an_entry_as_recently_used_so_it_survives_eviction() {
+        let mut cache = StmtCache::new(2);
+        cache.insert("a", &[], 1);
+        cache.insert("b", &[], 2);
+        // Touch "a" so "b" becomes the least-recently-used entry instead.
+        assert_eq!(cache.get("a", &[]), Some(1));
+        let evicted = cache.insert("c", &[], 3);
+        assert_eq!(evicted, Some(2));
+        assert_eq!(cache.get("a", &[]), Some(1));
+        assert_eq!(cache.get("c", &[]), Some(3));
+    }
+}
@@ -0,0 +1,223 @@
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use pyo3::PyErr;
+use tabby::RowWriter;
+use tokio::task::JoinHandle;
+
+use crate::row_writer::{ColumnInfo, CompactValue};
+
+/// How many frames a `StreamingRowWriter` may have in flight before `send`
+/// blocks and parsing pauses. Picked to smooth over normal row-to-row
+/// variance without letting a stalled consumer buffer an unbounded amount of
+/// server output in memory - the whole point of this mode.
+const STREAM_CHANNEL_CAPACITY: usize = 256;
+
+/// One unit of a streamed result, cut at the same points `MultiSetWriter`
+/// cuts a buffered one: a metadata frame opens a result set, row frames
+/// carry its data, and info frames ride alongside like an async
+/// MessagePack-RPC server pushing notifications ahead of its final reply.
+/// `End` is the terminal frame - once a consumer sees it there are no more
+/// frames for this stream, ever.
+pub enum StreamFrame {
+    Metadata(Vec<ColumnInfo>),
+    Row(Vec<CompactValue>),
+    Info { number: u32, message: String },
+    End,
+}
+
+/// A `RowWriter` that pushes each parsed frame onto a bounded channel
+/// instead of buffering it into a `RowSet`, so a consumer can start pulling
+/// rows before the TDS stream finishes arriving. The channel bound is the
+/// backpressure knob: once a slow consumer lets it fill up, `send` blocks
+/// and the TDS parse loop pauses with it, the same way a bounded
+/// MessagePack-RPC transport stalls a writer until its peer reads.
+pub struct StreamingRowWriter {
+    tx: SyncSender<StreamFrame>,
+    current_row: Vec<CompactValue>,
+    col_count: usize,
+    /// Set by the first `on_metadata`, whatever its column count.
+    started: bool,
+    /// Set once `send` finds the receiver gone (consumer dropped the
+    /// stream early); further frames are discarded instead of panicking on
+    /// a `send` that can never succeed again.
+    closed: bool,
+}
+
+impl StreamingRowWriter {
+    pub fn new() -> (Self, Receiver<StreamFrame>) {
+        let (tx, rx) = sync_channel(STREAM_CHANNEL_CAPACITY);
+        (Self { tx, current_row: Vec::new(), col_count: 0, started: false, closed: false }, rx)
+    }
+
+    fn send(&mut self, frame: StreamFrame) {
+        if self.closed {
+            return;
+        }
+        if self.tx.send(frame).is_err() {
+            self.closed = true;
+        }
+    }
+
+    /// `true` once the first `Metadata` frame has gone out - i.e. the TDS
+    /// response has started arriving. A batch that fails before this point
+    /// hasn't shown the consumer anything yet, so it's safe for the caller
+    /// to retry on a fresh client/writer; past this point a retry would
+    /// re-send a result set the consumer may already be partway through.
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    /// Signal that the batch finished successfully and no more frames are
+    /// coming. Mirrors `MultiSetWriter::finalize` cutting the stream off -
+    /// `StreamCursor::next_frame` turns this into `None` for the consumer.
+    pub fn finish(&mut self) {
+        self.send(StreamFrame::End);
+    }
+
+    #[inline]
+    fn set_cell(&mut self, col: usize, val: CompactValue) {
+        if col < self.current_row.len() {
+            self.current_row[col] = val;
+        }
+    }
+}
+
+impl RowWriter for StreamingRowWriter {
+    fn on_metadata(&mut self, columns: &[tabby::Column]) {
+        self.col_count = columns.len();
+        self.started = true;
+        self.current_row = vec![CompactValue::Null; self.col_count];
+        let cols = columns.iter().map(|c| ColumnInfo { name: c.name().to_string() }).collect();
+        self.send(StreamFrame::Metadata(cols));
+    }
+
+    fn on_row_done(&mut self) {
+        if self.col_count == 0 {
+            return;
+        }
+        let row = std::mem::replace(&mut self.current_row, vec![CompactValue::Null; self.col_count]);
+        self.send(StreamFrame::Row(row));
+    }
+
+    fn on_info(&mut self, number: u32, message: &str) {
+        self.send(StreamFrame::Info { number, message: message.to_string() });
+    }
+
+    #[inline] fn write_null(&mut self, col: usize) { self.set_cell(col, CompactValue::Null); }
+    #[inline] fn write_bool(&mut self, col: usize, val: bool) { self.set_cell(col, CompactValue::Bool(val)); }
+    #[inline] fn write_u8(&mut self, col: usize, val: u8) { self.set_cell(col, CompactValue::I64(val as i64)); }
+    #[inline] fn write_i16(&mut self, col: usize, val: i16) { self.set_cell(col, CompactValue::I64(val as i64)); }
+    #[inline] fn write_i32(&mut self, col: usize, val: i32) { self.set_cell(col, CompactValue::I64(val as i64)); }
+    #[inline] fn write_i64(&mut self, col: usize, val: i64) { self.set_cell(col, CompactValue::I64(val)); }
+    #[inline] fn write_f32(&mut self, col: usize, val: f32) { self.set_cell(col, CompactValue::F64(val as f64)); }
+    #[inline] fn write_f64(&mut self, col: usize, val: f64) { self.set_cell(col, CompactValue::F64(val)); }
+    #[inline] fn write_str(&mut self, col: usize, val: &str) { self.set_cell(col, CompactValue::Str(val.to_owned())); }
+    #[inline] fn write_bytes(&mut self, col: usize, val: &[u8]) { self.set_cell(col, CompactValue::Bytes(val.to_owned())); }
+    #[inline] fn write_date(&mut self, col: usize, days: i32) { self.set_cell(col, CompactValue::Date(days)); }
+    #[inline] fn write_time(&mut self, col: usize, nanos: i64) { self.set_cell(col, CompactValue::Time(nanos)); }
+    #[inline] fn write_datetime(&mut self, col: usize, micros: i64) { self.set_cell(col, CompactValue::DateTime(micros)); }
+    #[inline] fn write_datetimeoffset(&mut self, col: usize, micros: i64, offset_minutes: i16) { self.set_cell(col, CompactValue::DateTimeOffset(micros, offset_minutes)); }
+    #[inline] fn write_decimal(&mut self, col: usize, value: i128, precision: u8, scale: u8) { self.set_cell(col, CompactValue::Decimal(value, precision, scale)); }
+    #[inline] fn write_guid(&mut self, col: usize, bytes: &[u8; 16]) { self.set_cell(col, CompactValue::Guid(*bytes)); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_started_flips_only_after_metadata() {
+        let (mut w, _rx) = StreamingRowWriter::new();
+        assert!(!w.has_started());
+        w.on_metadata(&[]);
+        assert!(w.has_started());
+    }
+
+    #[test]
+    fn on_row_done_before_metadata_is_a_no_op() {
+        let (mut w, rx) = StreamingRowWriter::new();
+        w.on_row_done();
+        drop(w);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn write_then_row_done_sends_one_row_frame_with_the_cell() {
+        let (mut w, rx) = StreamingRowWriter::new();
+        // `on_metadata` takes `&[tabby::Column]`; empty metadata still sets
+        // `col_count` to 0, so drive `current_row` directly via `set_cell`
+        // to exercise the row path without needing a live `tabby::Column`.
+        w.current_row = vec![CompactValue::Null];
+        w.col_count = 1;
+        w.write_i64(0, 99);
+        w.on_row_done();
+
+        match rx.try_recv().expect("row frame") {
+            StreamFrame::Row(cells) => assert!(matches!(cells[0], CompactValue::I64(99))),
+            _ => panic!("expected a Row frame"),
+        }
+    }
+
+    #[test]
+    fn finish_sends_a_terminal_end_frame() {
+        let (mut w, rx) = StreamingRowWriter::new();
+        w.finish();
+        assert!(matches!(rx.try_recv().expect("end frame"), StreamFrame::End));
+    }
+
+    #[test]
+    fn send_after_receiver_drop_is_silently_discarded() {
+        let (mut w, rx) = StreamingRowWriter::new();
+        drop(rx);
+        w.finish(); // must not panic once the receiver is gone
+        assert!(w.closed);
+    }
+}
+
+/// A query run in streaming mode: the pooled client stays checked out on a
+/// dedicated blocking-pool thread (driving the TDS parse and feeding
+/// `StreamingRowWriter`'s channel) while `next_frame` lets Python pull
+/// frames one at a time on this side, awaiting the channel instead of the
+/// whole batch.
+pub struct StreamCursor {
+    rx: Arc<Mutex<Receiver<StreamFrame>>>,
+    task: Option<JoinHandle<Result<(), PyErr>>>,
+    done: bool,
+}
+
+impl StreamCursor {
+    pub fn new(rx: Receiver<StreamFrame>, task: JoinHandle<Result<(), PyErr>>) -> Self {
+        Self { rx: Arc::new(Mutex::new(rx)), task: Some(task), done: false }
+    }
+
+    /// Pull the next frame, blocking (off the async runtime's worker
+    /// thread) until one arrives. Returns `None` once `End` has come
+    /// through - after that, the background task is joined so a failed
+    /// batch surfaces its error here instead of being swallowed.
+    pub async fn next_frame(&mut self) -> Result<Option<StreamFrame>, PyErr> {
+        if self.done {
+            return Ok(None);
+        }
+        let rx = self.rx.clone();
+        let frame = tokio::task::spawn_blocking(move || rx.lock().recv().ok())
+            .await
+            .expect("stream receiver thread panicked");
+
+        match frame {
+            None | Some(StreamFrame::End) => {
+                self.done = true;
+                if let Some(task) = self.task.take() {
+                    match task.await {
+                        Ok(Err(e)) => return Err(e),
+                        Ok(Ok(())) => {}
+                        Err(e) => panic!("stream query task panicked: {e}"),
+                    }
+                }
+                Ok(None)
+            }
+            Some(other) => Ok(Some(other)),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyBool, PyBytes, PyFloat, PyInt, PyString};
+use std::borrow::Cow;
 use std::cell::RefCell;
+use tabby::ColumnData;
 use crate::row_writer::CompactValue;
 
 thread_local! {
@@ -143,7 +145,130 @@ pub fn compact_value_to_py(py: Python<'_>, val: &CompactValue) -> PyResult<PyObj
     }
 }
 
+/// Inverse of `micros_to_components`'s date math: days since the Unix epoch
+/// for a given civil (year, month, day), using the same Howard Hinnant
+/// algorithm so round-tripping through `compact_value_to_py`'s `Date` arm
+/// is exact.
+#[inline]
+fn days_from_civil(year: i32, month: u32, day: u32) -> i32 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = (y - era * 400) as u32;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe as i32 - 719468
+}
+
+/// Decode a `decimal.Decimal`'s `as_tuple()` (sign, digits, exponent) into
+/// the `(unscaled value, precision, scale)` triple `ColumnData::Numeric` expects.
+fn decimal_to_value_scale(param: &Bound<'_, PyAny>) -> PyResult<(i128, u8, u8)> {
+    let tuple = param.call_method0("as_tuple")?;
+    let sign: i64 = tuple.get_item(0)?.extract()?;
+    let digits: Vec<i64> = tuple.get_item(1)?.extract()?;
+    let exponent: i64 = tuple.get_item(2)?.extract()?;
+
+    let mut value: i128 = 0;
+    for d in &digits {
+        value = value * 10 + *d as i128;
+    }
+    // SQL Server's `Numeric`/`Decimal` types cap scale at 38. Rather than
+    // clamp it and ship `value` at a coarser scale than the Decimal actually
+    // has (silently off by a power of ten), reject it up front - the caller
+    // gets a clear error instead of a server-bound value that's wrong.
+    let scale = if exponent < 0 { -exponent } else { 0 };
+    if scale > 38 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Decimal has {} digits after the decimal point, which exceeds SQL Server's maximum scale of 38",
+            scale
+        )));
+    }
+    let scale = scale as u8;
+    if exponent > 0 {
+        value = value.saturating_mul(10i128.saturating_pow(exponent as u32));
+    }
+    if sign == 1 {
+        value = -value;
+    }
+    let precision = (digits.len() as u8).max(scale + 1).min(38);
+    Ok((value, precision, scale))
+}
+
+/// Convert a Python parameter into a typed TDS `ColumnData` for `sp_executesql`
+/// RPC binding. Mirrors `py_to_sql_literal`'s type dispatch but produces a
+/// real typed parameter instead of a SQL-literal string, so the batch this
+/// feeds is injection-proof, plan-cache friendly, and never rewrites the SQL
+/// text: unlike string substitution, a value containing `@pN` text can't be
+/// picked up by a later placeholder replacement.
+pub fn py_to_tds_param(py: Python<'_>, param: &Bound<'_, PyAny>) -> PyResult<ColumnData<'static>> {
+    if param.is_none() {
+        return Ok(ColumnData::None);
+    }
+    if param.is_instance_of::<PyBool>() {
+        let v: bool = param.extract()?;
+        return Ok(ColumnData::Bit(v));
+    }
+    if param.is_instance_of::<PyInt>() {
+        let v: i64 = param.extract()?;
+        return Ok(ColumnData::I64(v));
+    }
+    if param.is_instance_of::<PyFloat>() {
+        let v: f64 = param.extract()?;
+        return Ok(ColumnData::F64(v));
+    }
+    let is_decimal = with_decimal_cls(py, |_py, cls| param.is_instance(cls))?;
+    if is_decimal {
+        let (value, precision, scale) = decimal_to_value_scale(param)?;
+        return Ok(ColumnData::Numeric(value, precision, scale));
+    }
+    let is_datetime = with_datetime(py, |_py, cache| param.is_instance(cache.datetime_cls.bind(py)))?;
+    if is_datetime {
+        let year: i32 = param.getattr("year")?.extract()?;
+        let month: u32 = param.getattr("month")?.extract()?;
+        let day: u32 = param.getattr("day")?.extract()?;
+        let hour: i64 = param.getattr("hour")?.extract()?;
+        let minute: i64 = param.getattr("minute")?.extract()?;
+        let second: i64 = param.getattr("second")?.extract()?;
+        let microsecond: i64 = param.getattr("microsecond")?.extract()?;
+        let days = days_from_civil(year, month, day) as i64;
+        let micros = days * 86_400_000_000 + (hour * 3600 + minute * 60 + second) * 1_000_000 + microsecond;
+        return Ok(ColumnData::DateTime2(micros));
+    }
+    let is_date = with_datetime(py, |_py, cache| param.is_instance(cache.date_cls.bind(py)))?;
+    if is_date {
+        let year: i32 = param.getattr("year")?.extract()?;
+        let month: u32 = param.getattr("month")?.extract()?;
+        let day: u32 = param.getattr("day")?.extract()?;
+        return Ok(ColumnData::Date(days_from_civil(year, month, day)));
+    }
+    let is_time = with_datetime(py, |_py, cache| param.is_instance(cache.time_cls.bind(py)))?;
+    if is_time {
+        let hour: i64 = param.getattr("hour")?.extract()?;
+        let minute: i64 = param.getattr("minute")?.extract()?;
+        let second: i64 = param.getattr("second")?.extract()?;
+        let microsecond: i64 = param.getattr("microsecond")?.extract()?;
+        let nanos = (hour * 3600 + minute * 60 + second) * 1_000_000_000 + microsecond * 1000;
+        return Ok(ColumnData::Time(nanos));
+    }
+    let is_uuid = with_uuid_cls(py, |_py, cls| param.is_instance(cls))?;
+    if is_uuid {
+        let s: String = param.str()?.extract()?;
+        let u = uuid::Uuid::parse_str(&s)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("invalid UUID: {}", e)))?;
+        return Ok(ColumnData::Guid(u));
+    }
+    if param.is_instance_of::<PyBytes>() {
+        let v: Vec<u8> = param.extract()?;
+        return Ok(ColumnData::Binary(Cow::Owned(v)));
+    }
+    let s: String = param.str()?.extract()?;
+    Ok(ColumnData::String(Cow::Owned(s)))
+}
+
 /// Convert a Python parameter to a SQL literal string for substitution.
+///
+/// This is the legacy parameter path, kept for `Legacy Literal Params=true`
+/// connections; `py_to_tds_param`'s RPC path is the default.
 pub fn py_to_sql_literal(py: Python<'_>, param: &Bound<'_, PyAny>) -> PyResult<String> {
     if param.is_none() { return Ok("NULL".to_string()); }
     if param.is_instance_of::<PyBool>() {
@@ -202,3 +327,111 @@ pub fn py_to_sql_literal(py: Python<'_>, param: &Bound<'_, PyAny>) -> PyResult<S
     let s = param.str()?.to_string();
     Ok(format!("N'{}'", s.replace('\'', "''")))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_from_civil_round_trips_through_the_unix_epoch() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2000, 2, 29), days_from_civil(2000, 3, 1) - 1);
+    }
+
+    #[test]
+    fn decimal_i128_to_string_pads_and_places_the_point() {
+        assert_eq!(decimal_i128_to_string(12345, 2), "123.45");
+        assert_eq!(decimal_i128_to_string(12345, 0), "12345");
+        assert_eq!(decimal_i128_to_string(5, 4), "0.0005");
+        assert_eq!(decimal_i128_to_string(-12345, 2), "-123.45");
+    }
+
+    fn with_decimal<R>(s: &str, f: impl FnOnce(Python<'_>, &Bound<'_, PyAny>) -> R) -> R {
+        Python::with_gil(|py| {
+            let cls = py.import("decimal").unwrap().getattr("Decimal").unwrap();
+            let d = cls.call1((s,)).unwrap();
+            f(py, &d)
+        })
+    }
+
+    #[test]
+    fn decimal_to_value_scale_reads_unscaled_value_and_scale() {
+        with_decimal("123.45", |_py, d| {
+            let (value, precision, scale) = decimal_to_value_scale(d).unwrap();
+            assert_eq!(value, 12345);
+            assert_eq!(scale, 2);
+            assert_eq!(precision, 5);
+        });
+    }
+
+    #[test]
+    fn decimal_to_value_scale_handles_negative_and_integral_decimals() {
+        with_decimal("-42", |_py, d| {
+            let (value, _precision, scale) = decimal_to_value_scale(d).unwrap();
+            assert_eq!(value, -42);
+            assert_eq!(scale, 0);
+        });
+    }
+
+    #[test]
+    fn decimal_to_value_scale_rejects_scale_beyond_sql_servers_max() {
+        with_decimal("1E-39", |_py, d| {
+            let err = decimal_to_value_scale(d).unwrap_err();
+            assert!(err.to_string().contains("exceeds SQL Server's maximum scale of 38"));
+        });
+    }
+
+    #[test]
+    fn decimal_to_value_scale_accepts_exactly_38_digits_of_scale() {
+        with_decimal("1E-38", |_py, d| {
+            let (_value, _precision, scale) = decimal_to_value_scale(d).unwrap();
+            assert_eq!(scale, 38);
+        });
+    }
+
+    #[test]
+    fn py_to_tds_param_converts_decimal_into_numeric_column_data() {
+        Python::with_gil(|py| {
+            let cls = py.import("decimal").unwrap().getattr("Decimal").unwrap();
+            let d = cls.call1(("7.50",)).unwrap();
+            match py_to_tds_param(py, &d).unwrap() {
+                ColumnData::Numeric(value, _precision, scale) => {
+                    assert_eq!(value, 750);
+                    assert_eq!(scale, 2);
+                }
+                _ => panic!("expected ColumnData::Numeric"),
+            }
+        });
+    }
+
+    #[test]
+    fn py_to_tds_param_converts_uuid_into_guid_column_data() {
+        Python::with_gil(|py| {
+            let cls = py.import("uuid").unwrap().getattr("UUID").unwrap();
+            let u = cls.call1(("12345678-1234-5678-1234-567812345678",)).unwrap();
+            match py_to_tds_param(py, &u).unwrap() {
+                ColumnData::Guid(uuid) => {
+                    assert_eq!(uuid.to_string(), "12345678-1234-5678-1234-567812345678");
+                }
+                _ => panic!("expected ColumnData::Guid"),
+            }
+        });
+    }
+
+    #[test]
+    fn py_to_tds_param_converts_datetime_into_datetime2_micros() {
+        Python::with_gil(|py| {
+            let cls = py.import("datetime").unwrap().getattr("datetime").unwrap();
+            let dt = cls.call1((2024, 1, 2, 3, 4, 5, 6)).unwrap();
+            match py_to_tds_param(py, &dt).unwrap() {
+                ColumnData::DateTime2(micros) => {
+                    let days = days_from_civil(2024, 1, 2) as i64;
+                    let expected = days * 86_400_000_000 + (3 * 3600 + 4 * 60 + 5) * 1_000_000 + 6;
+                    assert_eq!(micros, expected);
+                }
+                _ => panic!("expected ColumnData::DateTime2"),
+            }
+        });
+    }
+}